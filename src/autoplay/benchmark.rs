@@ -0,0 +1,110 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::constants::SIMULATION_STEP;
+use crate::resources::{GameMode, NextSeed, ScoreTracker};
+use crate::simulation::headless_app;
+use crate::AppState;
+
+use super::{strategy_by_name, AutoPlayer, AutoPlayerPlugin, STRATEGIES};
+
+// an hour of play, in case a strategy never fills the container
+const BENCHMARK_MAX_FRAMES: u32 = 60 * 60 * 60;
+
+pub struct BenchmarkRun {
+    pub seed: u64,
+    pub score: u32,
+    pub largest_tier: usize,
+    pub drops: u32,
+    // left out of the mean and median
+    pub truncated: bool,
+}
+
+pub struct BenchmarkReport {
+    pub strategy: &'static str,
+    pub runs: Vec<BenchmarkRun>,
+}
+
+impl BenchmarkReport {
+    fn finished_scores(&self) -> Vec<u32> {
+        self.runs
+            .iter()
+            .filter(|run| !run.truncated)
+            .map(|run| run.score)
+            .collect()
+    }
+
+    pub fn truncated_runs(&self) -> usize {
+        self.runs.iter().filter(|run| run.truncated).count()
+    }
+
+    pub fn mean_score(&self) -> f32 {
+        let scores = self.finished_scores();
+        if scores.is_empty() {
+            return 0.0;
+        }
+        scores.iter().map(|score| *score as f32).sum::<f32>() / scores.len() as f32
+    }
+
+    pub fn median_score(&self) -> f32 {
+        let mut scores = self.finished_scores();
+        scores.sort_unstable();
+        match scores.len() {
+            0 => 0.0,
+            len if len % 2 == 0 => (scores[len / 2 - 1] + scores[len / 2]) as f32 / 2.0,
+            len => scores[len / 2] as f32,
+        }
+    }
+
+    pub fn highest_tier(&self) -> usize {
+        self.runs
+            .iter()
+            .map(|run| run.largest_tier)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+// None if there is no such strategy
+pub fn benchmark(strategy: &str, seeds: Range<u64>) -> Option<BenchmarkReport> {
+    let strategy = STRATEGIES.into_iter().find(|name| *name == strategy)?;
+    let runs = seeds
+        .map(|seed| {
+            let mut app = headless_app();
+            // one fixed step a frame, as fast as the machine goes
+            app.add_plugins(AutoPlayerPlugin)
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                    SIMULATION_STEP,
+                )))
+                .insert_resource(GameMode::Classic)
+                .insert_resource(NextSeed { seed: Some(seed) })
+                .insert_resource(AutoPlayer {
+                    enabled: true,
+                    strategy: strategy_by_name(strategy, seed)?,
+                });
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+            let mut truncated = true;
+            for _ in 0..BENCHMARK_MAX_FRAMES {
+                app.update();
+                if *app.world.resource::<State<AppState>>().get() == AppState::GameOverMenu {
+                    truncated = false;
+                    break;
+                }
+            }
+            let score_tracker = app.world.resource::<ScoreTracker>();
+            Some(BenchmarkRun {
+                seed,
+                score: score_tracker.score,
+                largest_tier: score_tracker.largest_tier,
+                drops: score_tracker.drops,
+                truncated,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(BenchmarkReport { strategy, runs })
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::resources::{Aim, NextGenerator, ScoreTracker, SpawnTime};
+use crate::simulation::{DropFruit, MarkForDelete};
+use crate::{AppState, Fruit};
+
+mod strategies;
+pub use strategies::{
+    strategy_by_name, Board, BoardFruit, GreedyStrategy, LookaheadStrategy, RandomStrategy,
+    Strategy, STRATEGIES,
+};
+
+mod benchmark;
+pub use benchmark::{benchmark, BenchmarkReport, BenchmarkRun};
+
+// B turns it on and off in game, `--autoplay <strategy>` starts with it on
+pub struct AutoPlayerPlugin;
+
+impl Plugin for AutoPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoPlayer>()
+            .add_systems(Startup, autoplay_from_args)
+            .add_systems(
+                Update,
+                (
+                    toggle_autoplayer.run_if(resource_exists::<Input<KeyCode>>()),
+                    autoplay.after(toggle_autoplayer),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct AutoPlayer {
+    pub enabled: bool,
+    pub strategy: Box<dyn Strategy>,
+}
+
+impl Default for AutoPlayer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: Box::new(LookaheadStrategy),
+        }
+    }
+}
+
+fn autoplay_from_args(mut autoplayer: ResMut<AutoPlayer>) {
+    let args: Vec<String> = std::env::args().collect();
+    let name = args
+        .iter()
+        .position(|arg| arg == "--autoplay")
+        .and_then(|i| args.get(i + 1));
+    if let Some(name) = name {
+        match strategy_by_name(name, rand::random()) {
+            Some(strategy) => {
+                autoplayer.strategy = strategy;
+                autoplayer.enabled = true;
+            }
+            None => error!(
+                "unknown strategy {}, expected one of {}",
+                name,
+                STRATEGIES.join(", ")
+            ),
+        }
+    }
+}
+
+fn toggle_autoplayer(keyboard_input: Res<Input<KeyCode>>, mut autoplayer: ResMut<AutoPlayer>) {
+    if keyboard_input.just_pressed(KeyCode::B) {
+        autoplayer.enabled = !autoplayer.enabled;
+    }
+}
+
+// drops as soon as SpawnTime allows
+#[allow(clippy::too_many_arguments)]
+fn autoplay(
+    mut autoplayer: ResMut<AutoPlayer>,
+    spawn_time: Res<SpawnTime>,
+    next_generator: Res<NextGenerator>,
+    catalog: Res<FruitCatalog>,
+    fruits: Query<(&Fruit, &Transform), Without<MarkForDelete>>,
+    aim: Option<ResMut<Aim>>,
+    mut score_tracker: ResMut<ScoreTracker>,
+    mut drop_requests: EventWriter<DropFruit>,
+) {
+    if !autoplayer.enabled || !spawn_time.timer.finished() {
+        return;
+    }
+    let board = Board {
+        fruits: fruits
+            .iter()
+            .map(|(fruit, transform)| BoardFruit {
+                fruit: *fruit,
+                position: transform.translation.truncate(),
+            })
+            .collect(),
+        current: next_generator.current_fruit,
+        next: Some(next_generator.next_fruit),
+        catalog: &catalog,
+    };
+    let x = autoplayer.strategy.choose(&board);
+    // moves the preview along when there is one
+    if let Some(mut aim) = aim {
+        aim.x = x;
+    }
+    score_tracker.autoplayed = true;
+    drop_requests.send(DropFruit { x });
+}
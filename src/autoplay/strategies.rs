@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{
+    CONTAINER_BASE_OFFSET, CONTAINER_HEIGHT, CONTAINER_THICKNESS, CONTAINER_WIDTH,
+    GAME_OVER_HEIGHT, SCREEN_HEIGHT,
+};
+use crate::Fruit;
+
+// top of the container's base
+const FLOOR: f32 = -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET + CONTAINER_THICKNESS / 2.0;
+// evenly spaced drop positions, on top of one above each fruit
+const CANDIDATES: usize = 25;
+// how much the best follow-up drop counts for the lookahead
+const LOOKAHEAD_WEIGHT: f32 = 0.5;
+
+pub struct BoardFruit {
+    pub fruit: Fruit,
+    pub position: Vec2,
+}
+
+pub struct Board<'a> {
+    pub fruits: Vec<BoardFruit>,
+    pub current: Fruit,
+    // unknown on a board that was only thought ahead to
+    pub next: Option<Fruit>,
+    pub catalog: &'a FruitCatalog,
+}
+
+struct Landing {
+    height: f32,
+    on: Option<usize>,
+}
+
+impl<'a> Board<'a> {
+    fn radius(&self, fruit: &Fruit) -> f32 {
+        self.catalog.kind(fruit).size / 2.0
+    }
+
+    // fruits are treated as circles falling straight down, rolling off is ignored
+    fn landing(&self, fruit: &Fruit, x: f32) -> Landing {
+        let radius = self.radius(fruit);
+        let mut landing = Landing {
+            height: FLOOR + radius,
+            on: None,
+        };
+        for (i, other) in self.fruits.iter().enumerate() {
+            let reach = self.radius(&other.fruit) + radius;
+            let dx = other.position.x - x;
+            if dx.abs() >= reach {
+                continue;
+            }
+            let height = other.position.y + (reach * reach - dx * dx).sqrt();
+            if height > landing.height {
+                landing = Landing {
+                    height,
+                    on: Some(i),
+                };
+            }
+        }
+        landing
+    }
+
+    pub fn candidates(&self, fruit: &Fruit) -> Vec<f32> {
+        let span = CONTAINER_WIDTH / 2.0 - self.radius(fruit) - 1.0;
+        let mut candidates: Vec<f32> = (0..CANDIDATES)
+            .map(|i| -span + 2.0 * span * i as f32 / (CANDIDATES - 1) as f32)
+            .collect();
+        candidates.extend(
+            self.fruits
+                .iter()
+                .map(|other| other.position.x.clamp(-span, span)),
+        );
+        candidates
+    }
+
+    // a match is best, then staying low, burying a smaller fruit or piling up too high is penalised
+    pub fn placement_score(&self, fruit: &Fruit, x: f32) -> f32 {
+        let landing = self.landing(fruit, x);
+        let mut score = -(landing.height - FLOOR) / CONTAINER_HEIGHT;
+        if let Some(i) = landing.on {
+            let other = &self.fruits[i].fruit;
+            if other.tier == fruit.tier {
+                score += 2.0 + 0.1 * fruit.tier as f32;
+            } else if other.tier < fruit.tier {
+                score -= 0.5;
+            }
+        }
+        if landing.height + self.radius(fruit) > GAME_OVER_HEIGHT {
+            score -= 10.0;
+        }
+        score
+    }
+
+    // a match merges in place without setting off others, None if the fruit after this one isn't known
+    pub fn place(&self, fruit: Fruit, x: f32) -> Option<Board<'a>> {
+        let current = self.next?;
+        let landing = self.landing(&fruit, x);
+        let mut fruits: Vec<BoardFruit> = self
+            .fruits
+            .iter()
+            .map(|other| BoardFruit {
+                fruit: other.fruit,
+                position: other.position,
+            })
+            .collect();
+        match landing.on {
+            Some(i) if fruits[i].fruit == fruit => {
+                let merged = fruits.swap_remove(i);
+                if let Some(result) = fruit.merge(self.catalog) {
+                    fruits.push(BoardFruit {
+                        fruit: result,
+                        position: merged.position,
+                    });
+                }
+            }
+            _ => fruits.push(BoardFruit {
+                fruit,
+                position: Vec2::new(x, landing.height),
+            }),
+        }
+        Some(Board {
+            fruits,
+            current,
+            next: None,
+            catalog: self.catalog,
+        })
+    }
+
+    fn best_placement(&self, fruit: &Fruit) -> (f32, f32) {
+        self.candidates(fruit)
+            .into_iter()
+            .map(|x| (x, self.placement_score(fruit, x)))
+            .fold((0.0, f32::NEG_INFINITY), |best, option| {
+                if option.1 > best.1 {
+                    option
+                } else {
+                    best
+                }
+            })
+    }
+}
+
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn choose(&mut self, board: &Board) -> f32;
+}
+
+pub const STRATEGIES: [&str; 3] = ["random", "greedy", "lookahead"];
+
+// the seed only matters to the random one
+pub fn strategy_by_name(name: &str, seed: u64) -> Option<Box<dyn Strategy>> {
+    match name {
+        "random" => Some(Box::new(RandomStrategy::new(seed))),
+        "greedy" => Some(Box::new(GreedyStrategy)),
+        "lookahead" => Some(Box::new(LookaheadStrategy)),
+        _ => None,
+    }
+}
+
+// its own rng, so it doesn't change the fruits the run hands out
+pub struct RandomStrategy {
+    rng: ChaCha8Rng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn choose(&mut self, board: &Board) -> f32 {
+        let span = CONTAINER_WIDTH / 2.0 - board.radius(&board.current);
+        self.rng.gen_range(-span..=span)
+    }
+}
+
+pub struct GreedyStrategy;
+
+impl Strategy for GreedyStrategy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn choose(&mut self, board: &Board) -> f32 {
+        board.best_placement(&board.current).0
+    }
+}
+
+pub struct LookaheadStrategy;
+
+impl Strategy for LookaheadStrategy {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn choose(&mut self, board: &Board) -> f32 {
+        let mut best = (0.0, f32::NEG_INFINITY);
+        for x in board.candidates(&board.current) {
+            let follow_up = board
+                .place(board.current, x)
+                .map_or(0.0, |after| after.best_placement(&after.current).1);
+            let score = board.placement_score(&board.current, x) + LOOKAHEAD_WEIGHT * follow_up;
+            if score > best.1 {
+                best = (x, score);
+            }
+        }
+        best.0
+    }
+}
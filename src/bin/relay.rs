@@ -0,0 +1,26 @@
+// `cargo run --bin relay -- [address]` serves `suika --race ws://<address>`
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use bevy::log::{error, info, Level};
+
+    // like bevy's LogPlugin: info and up, to stderr
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("0.0.0.0:9001"));
+    let listener = match std::net::TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("could not listen on {}: {}", address, err);
+            std::process::exit(1);
+        }
+    };
+    info!("race relay listening on {}", address);
+    suika::race::run_relay(listener);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
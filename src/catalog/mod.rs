@@ -0,0 +1,322 @@
+use std::fmt;
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::BoxedFuture;
+use bevy_rapier2d::prelude::Collider;
+use serde::Deserialize;
+
+use crate::constants::LARGEST_FRUIT_SIZE;
+use crate::{AppState, Fruit};
+
+const DEFAULT_ROSTER: &str = "hololive.fruits.ron";
+
+// `--roster <file>` loads another *.fruits.ron than the default one
+pub struct CatalogPlugin;
+
+impl Plugin for CatalogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<FruitCatalog>()
+            .init_asset_loader::<FruitCatalogLoader>()
+            .add_systems(Startup, load_roster)
+            .add_systems(Update, apply_loaded_roster);
+    }
+}
+
+#[derive(Resource, Deserialize, TypeUuid, TypePath, Clone)]
+#[uuid = "5b7d9a4e-2f61-4c1b-9d8e-3a0c6f1e7b42"]
+pub struct FruitCatalog {
+    // only the first `droppable` tiers are ever handed to the player
+    pub droppable: usize,
+    pub fruits: Vec<FruitKind>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct FruitKind {
+    pub name: String,
+    pub size: f32,
+    pub score: u32,
+    pub texture: String,
+    #[serde(default)]
+    pub sound: Option<String>,
+    #[serde(default)]
+    pub collider: FruitShape,
+    // rgb of the particles when this fruit merges
+    #[serde(default)]
+    pub tint: Option<[f32; 3]>,
+}
+
+impl FruitKind {
+    pub fn tint(&self) -> Color {
+        match self.tint {
+            Some([r, g, b]) => Color::rgb(r, g, b),
+            None => Color::WHITE,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum FruitShape {
+    #[default]
+    Ball,
+    Capsule,
+    Square,
+}
+
+impl FruitShape {
+    pub fn collider(&self, size: f32) -> Collider {
+        match self {
+            FruitShape::Ball => Collider::ball(size / 2.0),
+            FruitShape::Capsule => Collider::capsule_y(size * 0.15, size * 0.35),
+            FruitShape::Square => Collider::round_cuboid(size * 0.4, size * 0.4, size * 0.1),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Parse(ron::error::SpannedError),
+    Empty,
+    BadDroppable(usize),
+    BadSize(String, f32),
+    DuplicateName(String),
+    MissingTexture(String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::Parse(err) => write!(f, "invalid fruit catalog: {}", err),
+            CatalogError::Empty => write!(f, "fruit catalog has no fruits"),
+            CatalogError::BadDroppable(droppable) => write!(
+                f,
+                "droppable must be between 1 and the number of fruits, got {}",
+                droppable
+            ),
+            CatalogError::BadSize(name, size) => write!(
+                f,
+                "{} has size {}, sizes must be positive and at most {}",
+                name, size, LARGEST_FRUIT_SIZE
+            ),
+            CatalogError::DuplicateName(name) => write!(f, "{} appears more than once", name),
+            CatalogError::MissingTexture(name) => write!(f, "{} has no texture", name),
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+impl Default for FruitCatalog {
+    fn default() -> Self {
+        Self::from_ron(include_str!("../../assets/hololive.fruits.ron"))
+            .expect("built-in fruit catalog is valid")
+    }
+}
+
+impl FruitCatalog {
+    pub fn from_ron(ron: &str) -> Result<Self, CatalogError> {
+        let catalog: FruitCatalog = ron::from_str(ron).map_err(CatalogError::Parse)?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    pub fn validate(&self) -> Result<(), CatalogError> {
+        if self.fruits.is_empty() {
+            return Err(CatalogError::Empty);
+        }
+        if self.droppable == 0 || self.droppable > self.fruits.len() {
+            return Err(CatalogError::BadDroppable(self.droppable));
+        }
+        for (tier, kind) in self.fruits.iter().enumerate() {
+            if kind.size <= 0.0 || kind.size > LARGEST_FRUIT_SIZE {
+                return Err(CatalogError::BadSize(kind.name.clone(), kind.size));
+            }
+            if kind.texture.is_empty() {
+                return Err(CatalogError::MissingTexture(kind.name.clone()));
+            }
+            if self.fruits[..tier]
+                .iter()
+                .any(|other| other.name == kind.name)
+            {
+                return Err(CatalogError::DuplicateName(kind.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self, fruit: &Fruit) -> &FruitKind {
+        &self.fruits[fruit.tier]
+    }
+
+    pub fn largest_tier(&self) -> usize {
+        self.fruits.len() - 1
+    }
+}
+
+#[derive(Default)]
+pub struct FruitCatalogLoader;
+
+impl AssetLoader for FruitCatalogLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let catalog = FruitCatalog::from_ron(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(catalog));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fruits.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct RosterHandle(Handle<FruitCatalog>);
+
+fn load_roster(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let args: Vec<String> = std::env::args().collect();
+    let roster = args
+        .iter()
+        .position(|arg| arg == "--roster")
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_ROSTER);
+    commands.insert_resource(RosterHandle(asset_server.load(roster)));
+}
+
+// other tiers would break the fruits on the board and the queue, so those wait for an empty start menu
+fn apply_loaded_roster(
+    mut catalog_events: EventReader<AssetEvent<FruitCatalog>>,
+    mut pending: Local<bool>,
+    roster: Res<RosterHandle>,
+    catalogs: Res<Assets<FruitCatalog>>,
+    mut catalog: ResMut<FruitCatalog>,
+    state: Res<State<AppState>>,
+    fruits: Query<(), With<Fruit>>,
+) {
+    for event in catalog_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle }
+                if *handle == roster.0 =>
+            {
+                *pending = true;
+            }
+            _ => (),
+        }
+    }
+    if !*pending {
+        return;
+    }
+    if let Some(loaded) = catalogs.get(&roster.0) {
+        let between_runs = *state.get() == AppState::StartMenu && fruits.is_empty();
+        if loaded.fruits.len() == catalog.fruits.len() || between_runs {
+            *catalog = loaded.clone();
+            *pending = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog(droppable: usize, fruits: &[(&str, f32, &str)]) -> String {
+        let fruits: Vec<String> = fruits
+            .iter()
+            .map(|(name, size, texture)| {
+                format!(
+                    "(name: \"{}\", size: {:?}, score: 1, texture: \"{}\")",
+                    name, size, texture
+                )
+            })
+            .collect();
+        format!(
+            "(droppable: {}, fruits: [{}])",
+            droppable,
+            fruits.join(", ")
+        )
+    }
+
+    #[test]
+    fn built_in_catalog_is_valid() {
+        let catalog = FruitCatalog::default();
+        assert_eq!(catalog.fruits.len(), 11);
+        assert_eq!(catalog.largest_tier(), 10);
+        assert_eq!(catalog.kind(&Fruit { tier: 0 }).name, "gura");
+    }
+
+    #[test]
+    fn optional_fields_have_defaults() {
+        let catalog =
+            FruitCatalog::from_ron(&catalog(1, &[("a", 10.0, "a.png"), ("b", 20.0, "b.png")]))
+                .unwrap();
+        assert_eq!(catalog.droppable, 1);
+        assert!(catalog.fruits[0].sound.is_none());
+        assert!(matches!(catalog.fruits[0].collider, FruitShape::Ball));
+        assert_eq!(catalog.fruits[1].tint(), Color::WHITE);
+    }
+
+    #[test]
+    fn unparsable_catalog_is_rejected() {
+        assert!(matches!(
+            FruitCatalog::from_ron("(droppable: 1, fruits: [(name: \"a\")])"),
+            Err(CatalogError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn empty_catalog_is_rejected() {
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(1, &[])),
+            Err(CatalogError::Empty)
+        ));
+    }
+
+    #[test]
+    fn droppable_has_to_be_within_the_fruits() {
+        let fruits = [("a", 10.0, "a.png"), ("b", 20.0, "b.png")];
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(0, &fruits)),
+            Err(CatalogError::BadDroppable(0))
+        ));
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(3, &fruits)),
+            Err(CatalogError::BadDroppable(3))
+        ));
+        assert!(FruitCatalog::from_ron(&catalog(2, &fruits)).is_ok());
+    }
+
+    #[test]
+    fn sizes_have_to_be_positive_and_fit() {
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(1, &[("a", 0.0, "a.png")])),
+            Err(CatalogError::BadSize(name, size)) if name == "a" && size == 0.0
+        ));
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(1, &[("a", LARGEST_FRUIT_SIZE + 1.0, "a.png")])),
+            Err(CatalogError::BadSize(..))
+        ));
+        assert!(FruitCatalog::from_ron(&catalog(1, &[("a", LARGEST_FRUIT_SIZE, "a.png")])).is_ok());
+    }
+
+    #[test]
+    fn names_have_to_be_unique() {
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(1, &[("a", 10.0, "a.png"), ("a", 20.0, "b.png")])),
+            Err(CatalogError::DuplicateName(name)) if name == "a"
+        ));
+    }
+
+    #[test]
+    fn every_fruit_needs_a_texture() {
+        assert!(matches!(
+            FruitCatalog::from_ron(&catalog(1, &[("a", 10.0, "")])),
+            Err(CatalogError::MissingTexture(name)) if name == "a"
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+// the browser's clipboard api only reports failure later, in the console
+
+// on X11 and Wayland the copied text is gone once the arboard clipboard is dropped
+#[derive(Default)]
+pub struct Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    native: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        let clipboard = match &mut self.native {
+            Some(clipboard) => clipboard,
+            None => self
+                .native
+                .insert(arboard::Clipboard::new().map_err(|err| err.to_string())?),
+        };
+        clipboard.set_text(text).map_err(|err| err.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        use js_sys::{Function, Reflect};
+
+        // web-sys only has navigator.clipboard behind its unstable apis flag
+        let navigator = web_sys::window().ok_or("no window")?.navigator();
+        let clipboard = Reflect::get(&navigator, &"clipboard".into())
+            .ok()
+            .filter(|clipboard| !clipboard.is_undefined())
+            .ok_or("no clipboard access")?;
+        let write_text = Reflect::get(&clipboard, &"writeText".into())
+            .map_err(|_| String::from("no clipboard access"))?;
+        Function::from(write_text)
+            .call1(&clipboard, &text.into())
+            .map(|_| ())
+            .map_err(|_| String::from("could not write to the clipboard"))
+    }
+}
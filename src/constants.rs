@@ -1,5 +1,6 @@
 use bevy::prelude::Color;
 
+// the camera scales the world to whatever the window is
 pub const SCREEN_WIDTH: f32 = 1200.0;
 pub const SCREEN_HEIGHT: f32 = 800.0;
 
@@ -8,54 +9,44 @@ pub const CONTAINER_HEIGHT: f32 = 500.0;
 pub const CONTAINER_THICKNESS: f32 = 20.0;
 pub const CONTAINER_BASE_OFFSET: f32 = 25.0;
 pub const SPAWN_OFFSET: f32 =
-    CONTAINER_BASE_OFFSET + CONTAINER_HEIGHT + CONTAINER_THICKNESS + KNOWN_TYPES[10].0 / 2.0;
+    CONTAINER_BASE_OFFSET + CONTAINER_HEIGHT + CONTAINER_THICKNESS + LARGEST_FRUIT_SIZE / 2.0;
 pub const SPAWN_HEIGHT: f32 = -SCREEN_HEIGHT / 2.0 + SPAWN_OFFSET;
 pub const GAME_OVER_HEIGHT: f32 =
     -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET + CONTAINER_HEIGHT + CONTAINER_THICKNESS;
 pub const NEXT_PREVIEW_OFFSET: f32 = 180.0;
 pub const NEXT_PREVIEW_LABEL_SIZE: f32 = 40.0;
+// the hold preview's background is also what can be clicked
+pub const PREVIEW_PANEL_WIDTH: f32 = 150.0;
+pub const PREVIEW_PANEL_HEIGHT: f32 = 230.0;
+// the hold preview sits this far under the score in landscape
+pub const HOLD_PANEL_DROP: f32 = 150.0;
+// portrait puts the previews above the board and the merge guide below it
+pub const PORTRAIT_WIDTH: f32 = CONTAINER_WIDTH + 2.0 * CONTAINER_THICKNESS + 80.0;
+pub const PORTRAIT_TOP_ROW_HEIGHT: f32 = 260.0;
+pub const PORTRAIT_BOTTOM_ROW_HEIGHT: f32 = 120.0;
+pub const PORTRAIT_PANEL_OFFSET: f32 = 130.0;
 
-pub const SLIDER_POS_X_OFFSET: f32 = 150.0;
-pub const SLIDER_POS_Y_OFFSET: f32 = 100.0;
-pub const SLIDER_POS_X: f32 = SCREEN_WIDTH / 2.0 - SLIDER_POS_X_OFFSET;
-pub const SLIDER_POS_Y: f32 = -SCREEN_HEIGHT / 2.0 + SLIDER_POS_Y_OFFSET;
-pub const SLIDER_CONTAINER_WIDTH: f32 = 250.0;
-pub const SLIDER_CONTAINER_HEIGHT: f32 = 80.0;
+// the controls stack up from the bottom right corner of the window
+pub const CONTROLS_RIGHT: f32 = 25.0;
+pub const CONTROLS_BOTTOM: f32 = 60.0;
+pub const CONTROLS_GAP: f32 = 40.0;
+pub const CONTROL_WIDTH: f32 = 250.0;
+pub const CONTROL_HEIGHT: f32 = 80.0;
+// the menus stack their buttons down from the top right corner instead
+pub const MENU_CONTROLS_TOP: f32 = 25.0;
+pub const MENU_CONTROLS_GAP: f32 = 25.0;
+// in portrait the controls sit in a row under the board instead
+pub const PORTRAIT_CONTROLS_WIDTH: f32 = 3.0 * CONTROL_WIDTH + 4.0 * 20.0;
+pub const PORTRAIT_CONTROLS_HEIGHT: f32 = 180.0;
 pub const SLIDER_WIDTH: f32 = 225.0;
 pub const YAGOO_SIZE: f32 = 50.0;
-pub const SLIDER_CONTAINER_SIDES: (f32, f32, f32, f32) = (
-    SLIDER_POS_Y + SLIDER_CONTAINER_HEIGHT / 2.0,
-    SLIDER_POS_X + SLIDER_CONTAINER_WIDTH / 2.0,
-    SLIDER_POS_Y - SLIDER_CONTAINER_HEIGHT / 2.0,
-    SLIDER_POS_X - SLIDER_CONTAINER_WIDTH / 2.0,
-); // (top, right, bottom, left) or clockwise starting from top
-
-pub const NOISE_TOGGLE_POS_X_OFFSET: f32 = 150.0;
-pub const NOISE_TOGGLE_POS_Y_OFFSET: f32 = 250.0;
-pub const NOISE_TOGGLE_POS_X: f32 = SCREEN_WIDTH / 2.0 - NOISE_TOGGLE_POS_X_OFFSET;
-pub const NOISE_TOGGLE_POS_Y: f32 = -SCREEN_HEIGHT / 2.0 + NOISE_TOGGLE_POS_Y_OFFSET;
-pub const NOISE_TOGGLE_WIDTH: f32 = 250.0;
-pub const NOISE_TOGGLE_HEIGHT: f32 = 80.0;
-pub const NOISE_TOGGLE_SIDES: (f32, f32, f32, f32) = (
-    NOISE_TOGGLE_POS_Y + NOISE_TOGGLE_HEIGHT / 2.0,
-    NOISE_TOGGLE_POS_X + NOISE_TOGGLE_WIDTH / 2.0,
-    NOISE_TOGGLE_POS_Y - NOISE_TOGGLE_HEIGHT / 2.0,
-    NOISE_TOGGLE_POS_X - NOISE_TOGGLE_WIDTH / 2.0,
-); // (top, right, bottom, left) or clockwise starting from top
-
-pub const RESTART_POS_X_OFFSET: f32 = 150.0;
-pub const RESTART_POS_Y_OFFSET: f32 = 400.0;
-pub const RESTART_POS_X: f32 = SCREEN_WIDTH / 2.0 - RESTART_POS_X_OFFSET;
-pub const RESTART_POS_Y: f32 = -SCREEN_HEIGHT / 2.0 + RESTART_POS_Y_OFFSET;
-pub const RESTART_WIDTH: f32 = 250.0;
-pub const RESTART_HEIGHT: f32 = 80.0;
-pub const RESTART_SIDES: (f32, f32, f32, f32) = (
-    RESTART_POS_Y + RESTART_HEIGHT / 2.0,
-    RESTART_POS_X + RESTART_WIDTH / 2.0,
-    RESTART_POS_Y - RESTART_HEIGHT / 2.0,
-    RESTART_POS_X - RESTART_WIDTH / 2.0,
-); // (top, right, bottom, left) or clockwise starting from top
+// the versus arena is off to the side of the regular board
+pub const VERSUS_ARENA_X: f32 = 4.0 * SCREEN_WIDTH;
+pub const VERSUS_BOARD_OFFSET: f32 = 330.0;
+pub const VERSUS_WIDTH: f32 = 1500.0;
 
+// one fixed step of both the rules and the physics
+pub const SIMULATION_STEP: f32 = 1.0 / 60.0;
 pub const GRAVITY: f32 = 3.5;
 pub const RESTITUATION: f32 = 0.00000001;
 pub const MASS: f32 = 5.0;
@@ -71,19 +62,36 @@ pub const SCORE_TEXT_COLOR: Color = Color::rgb(0.0, 0.4667, 0.7137);
 pub const TEXT_COLOR: Color = Color::rgb(0.0118, 0.0157, 0.3686);
 pub const PREVIEW_HINT_COLOR: Color = Color::rgba(0.0118, 0.0157, 0.3686, 0.25);
 pub const GAME_OVER_HINT_COLOR: Color = Color::rgba(0.0, 0.7059, 0.8471, 0.3);
+pub const BUTTON_HOVERED_COLOR: Color = Color::rgb(0.4549, 0.8353, 0.9098);
+pub const BUTTON_PRESSED_COLOR: Color = Color::rgb(0.0, 0.7059, 0.8471);
+pub const BUTTON_FOCUSED_COLOR: Color = Color::rgb(0.2941, 0.7765, 0.8824);
+pub const HOLD_USED_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+pub const PAUSE_OVERLAY_COLOR: Color = Color::rgba(0.7922, 0.9412, 0.9725, 0.85);
+pub const DANGER_COLOR: Color = Color::rgb(0.9, 0.1, 0.15);
 pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
 
 pub const CLICK_DELAY: f32 = 0.8;
-pub const KNOWN_TYPES: [(f32, &str, u32); 11] = [
-    (31.2, "gura", 0),
-    (48.0, "aqua", 1),
-    (64.8, "hakos", 3),
-    (72.0, "towa", 6),
-    (92.4, "kobo", 10),
-    (110.4, "ayame", 15),
-    (116.4, "koyori", 21),
-    (154.8, "fubuki", 28),
-    (184.8, "mio", 36),
-    (208.8, "ollie", 45),
-    (244.8, "sana", 55),
-];
+// time attack runs last this many seconds, with a shorter wait between drops
+pub const TIME_ATTACK_LIMIT: f32 = 180.0;
+pub const TIME_ATTACK_CLICK_DELAY: f32 = 0.5;
+// how long a fruit may stay above the game over line before the run ends
+pub const GAME_OVER_GRACE: f32 = 2.0;
+// a freshly merged fruit continues a combo if it merges again within this many seconds
+pub const COMBO_WINDOW: f32 = 1.0;
+pub const COMBO_COLOR: Color = Color::rgb(0.9569, 0.4275, 0.1216);
+// merges into this tier or above send garbage, one more per tier above
+pub const GARBAGE_MIN_TIER: usize = 5;
+pub const GARBAGE_DELAY: f32 = 1.0;
+pub const GARBAGE_BATCH: u32 = 6;
+// fraction of the gap to the real score closed per second, and the slowest it rolls
+pub const SCORE_ROLL_RATE: f32 = 8.0;
+pub const SCORE_ROLL_MIN_SPEED: f32 = 30.0;
+// pixels per second, shift switches to the coarse one
+pub const AIM_FINE_SPEED: f32 = 150.0;
+pub const AIM_COARSE_SPEED: f32 = 600.0;
+// how long mouse input is ignored after a touch
+pub const TOUCH_MOUSE_GRACE: f32 = 0.5;
+// the layout leaves room for fruits up to this size, catalogs can't go above it
+pub const LARGEST_FRUIT_SIZE: f32 = 244.8;
+// height reserved for the fruit shown in the next preview
+pub const NEXT_PREVIEW_FRUIT_SIZE: f32 = 110.4;
@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::FruitCatalog;
+use crate::clipboard::Clipboard;
+use crate::constants::{CONTROLS_RIGHT, SCREEN_HEIGHT, TEXT_COLOR};
+use crate::layout::Layout;
+use crate::menu::{button_bundle, just_pressed, label_bundle, MenuItem};
+use crate::resources::{daily_seed_date, GameMode, GameRng, RecentTouch, ScoreTracker};
+use crate::simulation::GameOver;
+use crate::storage;
+use crate::AppState;
+
+const STORAGE_KEY: &str = "daily_bests";
+// days of bests kept around
+const MAX_DAYS: usize = 30;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DailyBests::load())
+            .init_resource::<LatestDaily>()
+            .init_non_send_resource::<Clipboard>()
+            .add_systems(
+                Update,
+                record_daily_result.run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnEnter(AppState::GameOverMenu), setup_daily_summary)
+            .add_systems(
+                Update,
+                share_daily_result.run_if(in_state(AppState::GameOverMenu)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyResult {
+    // also the date
+    pub seed: u64,
+    pub score: u32,
+    pub largest_tier: usize,
+}
+
+// newest day first
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct DailyBests {
+    pub days: Vec<DailyResult>,
+}
+
+impl DailyBests {
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn best(&self, seed: u64) -> Option<&DailyResult> {
+        self.days.iter().find(|day| day.seed == seed)
+    }
+
+    pub fn submit(&mut self, result: DailyResult) -> bool {
+        match self.days.iter_mut().find(|day| day.seed == result.seed) {
+            Some(best) if best.score >= result.score => return false,
+            Some(best) => *best = result,
+            None => {
+                self.days.push(result);
+                self.days.sort_by_key(|day| std::cmp::Reverse(day.seed));
+                self.days.truncate(MAX_DAYS);
+            }
+        }
+        true
+    }
+}
+
+// None after any other kind of run
+#[derive(Resource, Default)]
+struct LatestDaily {
+    result: Option<DailyResult>,
+    new_best: bool,
+}
+
+#[derive(Component)]
+struct ShareButton;
+
+#[derive(Component)]
+struct ShareText;
+
+pub fn share_text(result: &DailyResult, catalog: &FruitCatalog) -> String {
+    let largest = catalog
+        .fruits
+        .get(result.largest_tier)
+        .map_or("?", |kind| kind.name.as_str());
+    format!(
+        "suika daily {}\nscore: {}\nhighest fruit: {}",
+        daily_seed_date(result.seed),
+        result.score,
+        largest
+    )
+}
+
+fn record_daily_result(
+    mut game_over: EventReader<GameOver>,
+    score_tracker: Res<ScoreTracker>,
+    game_rng: Res<GameRng>,
+    mut daily_bests: ResMut<DailyBests>,
+    mut latest: ResMut<LatestDaily>,
+) {
+    for _ in game_over.iter() {
+        if score_tracker.mode != GameMode::Daily || score_tracker.autoplayed {
+            *latest = LatestDaily::default();
+            continue;
+        }
+        let result = DailyResult {
+            seed: game_rng.seed,
+            score: score_tracker.score,
+            largest_tier: score_tracker.largest_tier,
+        };
+        latest.new_best = daily_bests.submit(result.clone());
+        latest.result = Some(result);
+        daily_bests.save();
+    }
+}
+
+fn setup_daily_summary(
+    mut commands: Commands,
+    latest: Res<LatestDaily>,
+    daily_bests: Res<DailyBests>,
+) {
+    let Some(result) = &latest.result else {
+        return;
+    };
+    let best = daily_bests
+        .best(result.seed)
+        .map_or(result.score, |best| best.score);
+    let summary = if latest.new_best {
+        format!("best today: {} (new best!)  press S to share", best)
+    } else {
+        format!("best today: {}  press S to share", best)
+    };
+    commands.spawn((
+        MenuItem,
+        Text2dBundle {
+            text: Text::from_section(
+                summary,
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0 - 150.0, 1.0),
+            ..default()
+        },
+    ));
+
+    // under the mode button
+    let mut button = button_bundle();
+    button.style.position_type = PositionType::Absolute;
+    button.style.top = Val::Px(Layout::menu_control_top(1));
+    button.style.right = Val::Px(CONTROLS_RIGHT);
+    commands
+        .spawn((MenuItem, ShareButton, button))
+        .with_children(|builder| {
+            builder.spawn((ShareText, label_bundle("share")));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn share_daily_result(
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ShareButton>)>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    latest: Res<LatestDaily>,
+    catalog: Res<FruitCatalog>,
+    mut share_text_query: Query<&mut Text, With<ShareText>>,
+    mut clipboard: NonSendMut<Clipboard>,
+) {
+    let Some(result) = &latest.result else {
+        return;
+    };
+    let pressed = buttons
+        .iter()
+        .any(|interaction| just_pressed(interaction, &touches, &recent_touch));
+    if !pressed && !keyboard_input.just_pressed(KeyCode::S) {
+        return;
+    }
+
+    let label = match clipboard.copy(&share_text(result, &catalog)) {
+        Ok(()) => "copied!",
+        Err(err) => {
+            warn!("could not copy the daily result: {}", err);
+            "could not copy"
+        }
+    };
+    if let Ok(mut text) = share_text_query.get_single_mut() {
+        text.sections[0].value = String::from(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(seed: u64, score: u32) -> DailyResult {
+        DailyResult {
+            seed,
+            score,
+            largest_tier: 0,
+        }
+    }
+
+    #[test]
+    fn submit_keeps_the_best_run_of_each_day() {
+        let mut bests = DailyBests::default();
+        assert!(bests.submit(result(20240101, 100)));
+        assert!(!bests.submit(result(20240101, 80)));
+        // a tie doesn't replace the earlier run either
+        assert!(!bests.submit(result(20240101, 100)));
+        assert!(bests.submit(result(20240101, 150)));
+        assert_eq!(bests.days.len(), 1);
+        assert_eq!(bests.best(20240101).map(|day| day.score), Some(150));
+        assert!(bests.best(20240102).is_none());
+    }
+
+    #[test]
+    fn days_are_kept_newest_first() {
+        let mut bests = DailyBests::default();
+        bests.submit(result(20240131, 10));
+        bests.submit(result(20231231, 20));
+        bests.submit(result(20240201, 30));
+        let seeds: Vec<u64> = bests.days.iter().map(|day| day.seed).collect();
+        assert_eq!(seeds, [20240201, 20240131, 20231231]);
+    }
+
+    #[test]
+    fn only_the_latest_days_are_kept() {
+        let mut bests = DailyBests::default();
+        for day in 1..=MAX_DAYS as u64 + 1 {
+            bests.submit(result(20240100 + day, 10));
+        }
+        assert_eq!(bests.days.len(), MAX_DAYS);
+        assert!(bests.best(20240101).is_none());
+        // a day older than all of those doesn't make it in
+        bests.submit(result(20231231, 1_000));
+        assert!(bests.best(20231231).is_none());
+    }
+}
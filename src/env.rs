@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier2d::prelude::*;
+
+use crate::resources::{
+    DangerCountdown, GameMode, NextGenerator, NextSeed, ScoreTracker, SpawnTime,
+};
+use crate::simulation::{
+    at_rest, headless_app, settle_until, DropFruit, RestartGame, SettleConfig, TimeSinceSpawn,
+};
+use crate::{AppState, Fruit};
+
+#[derive(Clone, Debug)]
+pub struct FruitObservation {
+    pub fruit: Fruit,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Clone, Debug)]
+pub struct Observation {
+    pub fruits: Vec<FruitObservation>,
+    pub current: Fruit,
+    pub next: Fruit,
+    pub score: u32,
+}
+
+// a classic run driven one drop at a time, for training agents
+pub struct SuikaEnv {
+    app: App,
+    last_score: u32,
+}
+
+impl Default for SuikaEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuikaEnv {
+    pub fn new() -> Self {
+        let mut app = headless_app();
+        // resets step the same way as settling does
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            SettleConfig::default().step,
+        )))
+        .insert_resource(GameMode::Classic);
+        Self { app, last_score: 0 }
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        // lets a game over that was only just decided reach the GameOverMenu first
+        if self.is_done() && self.state() == AppState::InGame {
+            self.app.update();
+        }
+        self.app.world.resource_mut::<NextSeed>().seed = Some(seed);
+        if self.state() == AppState::InGame {
+            self.app.world.send_event(RestartGame);
+        } else {
+            self.app
+                .world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+        }
+        self.app.update();
+        self.last_score = 0;
+        self.observe()
+    }
+
+    // plays on until the board settles, the reward is the score gained on the way
+    pub fn step(&mut self, drop_x: f32) -> (Observation, f32, bool) {
+        if self.is_done() {
+            return (self.observe(), 0.0, true);
+        }
+        self.app.world.send_event(DropFruit { x: drop_x });
+        self.settle();
+
+        let score = self.app.world.resource::<ScoreTracker>().score;
+        let reward = score.saturating_sub(self.last_score) as f32;
+        self.last_score = score;
+        (self.observe(), reward, self.is_done())
+    }
+
+    // before the state has switched
+    pub fn is_done(&self) -> bool {
+        let next_state = self.app.world.resource::<NextState<AppState>>();
+        self.state() != AppState::InGame || next_state.0 == Some(AppState::GameOverMenu)
+    }
+
+    pub fn observe(&mut self) -> Observation {
+        let mut fruits = self.app.world.query::<(&Fruit, &Transform, &Velocity)>();
+        let fruits = fruits
+            .iter(&self.app.world)
+            .map(|(fruit, transform, velocity)| FruitObservation {
+                fruit: *fruit,
+                position: transform.translation.truncate(),
+                velocity: velocity.linvel,
+            })
+            .collect();
+        let next_generator = self.app.world.resource::<NextGenerator>();
+        Observation {
+            fruits,
+            current: next_generator.current_fruit,
+            next: next_generator.next_fruit,
+            score: self.app.world.resource::<ScoreTracker>().score,
+        }
+    }
+
+    fn state(&self) -> AppState {
+        self.app.world.resource::<State<AppState>>().get().clone()
+    }
+
+    // also waits for the next drop and for no fruit above the game over line
+    fn settle(&mut self) {
+        let config = SettleConfig::default();
+        settle_until(&mut self.app, &config, |world| {
+            let queued = world.resource::<NextState<AppState>>();
+            if queued.0 == Some(AppState::GameOverMenu) {
+                return true;
+            }
+            if !world.resource::<SpawnTime>().timer.finished()
+                || world.resource::<DangerCountdown>().elapsed.is_some()
+            {
+                return false;
+            }
+            let mut spawning = world.query_filtered::<(), With<TimeSinceSpawn>>();
+            spawning.iter(world).next().is_none() && at_rest(world, &config)
+        });
+    }
+}
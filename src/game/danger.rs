@@ -0,0 +1,64 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::constants::{DANGER_COLOR, GAME_OVER_HINT_COLOR};
+use crate::resources::{DangerCountdown, GameOverGrace, ReducedMotion};
+use crate::setup::{DangerText, GameOverLine};
+
+// pulses per second at the start and at the very end of the countdown
+const PULSE_START: f32 = 2.0;
+const PULSE_END: f32 = 6.0;
+
+pub fn update_danger_line(
+    countdown: Res<DangerCountdown>,
+    grace: Res<GameOverGrace>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time>,
+    mut line: Query<&mut Sprite, With<GameOverLine>>,
+    mut text: Query<(&mut Text, &mut Visibility), With<DangerText>>,
+) {
+    let Ok(mut sprite) = line.get_single_mut() else {
+        return;
+    };
+
+    match countdown.remaining(&grace) {
+        Some(remaining) => {
+            let progress = 1.0 - remaining / grace.seconds.max(f32::EPSILON);
+            let frequency = PULSE_START + (PULSE_END - PULSE_START) * progress;
+            // a steady bright line instead of the pulse with reduced motion
+            let pulse = if reduced_motion.is_on {
+                1.0
+            } else {
+                ((time.elapsed_seconds() * frequency * TAU).sin() + 1.0) / 2.0
+            };
+            // always mostly red, brighter and thicker on each pulse
+            sprite.color = mix(GAME_OVER_HINT_COLOR, DANGER_COLOR, 0.5 + pulse / 2.0);
+            if let Some(size) = sprite.custom_size.as_mut() {
+                size.y = 3.0 + 3.0 * pulse;
+            }
+            if let Ok((mut text, mut visibility)) = text.get_single_mut() {
+                text.sections[0].value = format!("{:.1}", remaining);
+                *visibility = Visibility::Inherited;
+            }
+        }
+        None => {
+            if sprite.color != GAME_OVER_HINT_COLOR {
+                sprite.color = GAME_OVER_HINT_COLOR;
+                if let Some(size) = sprite.custom_size.as_mut() {
+                    size.y = 3.0;
+                }
+            }
+            if let Ok((_, mut visibility)) = text.get_single_mut() {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+fn mix(from: Color, to: Color, amount: f32) -> Color {
+    let from = from.as_rgba_f32();
+    let to = to.as_rgba_f32();
+    let channel = |i: usize| from[i] + (to[i] - from[i]) * amount;
+    Color::rgba(channel(0), channel(1), channel(2), channel(3))
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{AIM_COARSE_SPEED, AIM_FINE_SPEED};
+use crate::resources::{Aim, NextGenerator};
+use crate::simulation::{pos_x_in_bounds, DropFruit, HoldFruit};
+
+pub fn keyboard_aim(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut aim: ResMut<Aim>,
+    next_generator: Res<NextGenerator>,
+    catalog: Res<FruitCatalog>,
+) {
+    let mut direction = 0.0;
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        direction -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        direction += 1.0;
+    }
+    if direction == 0.0 {
+        return;
+    }
+
+    let speed = if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        AIM_COARSE_SPEED
+    } else {
+        AIM_FINE_SPEED
+    };
+    // so holding a key against the wall doesn't build up an offset
+    aim.x = pos_x_in_bounds(
+        aim.x + direction * speed * time.delta_seconds(),
+        catalog.kind(&next_generator.current_fruit).size,
+    );
+}
+
+pub fn keyboard_drop(
+    keyboard_input: Res<Input<KeyCode>>,
+    aim: Res<Aim>,
+    mut hold_requests: EventWriter<HoldFruit>,
+    mut drop_requests: EventWriter<DropFruit>,
+) {
+    if keyboard_input.just_pressed(KeyCode::C) {
+        hold_requests.send(HoldFruit);
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+        drop_requests.send(DropFruit { x: aim.x });
+    }
+}
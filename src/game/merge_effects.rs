@@ -0,0 +1,182 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::resources::ReducedMotion;
+use crate::simulation::FruitsMerged;
+use crate::Fruit;
+
+const TWEEN_DURATION: f32 = 0.1;
+const POP_IN_DURATION: f32 = 0.35;
+const POP_IN_START_SCALE: f32 = 0.3;
+const PARTICLE_COUNT: usize = 12;
+const PARTICLE_LIFETIME: f32 = 0.45;
+const PARTICLE_SIZE: f32 = 9.0;
+
+// slides to where the new fruit appears
+#[derive(Component)]
+pub struct MergeTween {
+    from: Vec2,
+    to: Vec2,
+    size: f32,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct MergeParticle {
+    velocity: Vec2,
+    color: Color,
+    timer: Timer,
+}
+
+// by entity, the simulation may despawn a fruit at any time
+#[derive(Resource, Default)]
+pub struct PoppingFruits {
+    pub timers: HashMap<Entity, Timer>,
+}
+
+impl PoppingFruits {
+    pub fn scale(&self, entity: Entity) -> Option<f32> {
+        self.timers
+            .get(&entity)
+            .map(|timer| pop_in_scale(timer.percent()))
+    }
+}
+
+pub fn spawn_merge_effects(
+    mut commands: Commands,
+    mut fruits_merged: EventReader<FruitsMerged>,
+    mut popping: ResMut<PoppingFruits>,
+    reduced_motion: Res<ReducedMotion>,
+    asset_server: Res<AssetServer>,
+    catalog: Res<FruitCatalog>,
+) {
+    if reduced_motion.is_on {
+        fruits_merged.clear();
+        return;
+    }
+    // not the run's seeded rng
+    let mut rng = thread_rng();
+    for merged in fruits_merged.iter() {
+        let source = catalog.kind(&merged.source);
+        for from in merged.from {
+            commands.spawn((
+                MergeTween {
+                    from,
+                    to: merged.position,
+                    size: source.size,
+                    timer: Timer::from_seconds(TWEEN_DURATION, TimerMode::Once),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(1.0, 1.0) * source.size),
+                        ..default()
+                    },
+                    texture: asset_server.load(&source.texture),
+                    transform: Transform::from_translation(from.extend(1.0)),
+                    ..default()
+                },
+            ));
+        }
+
+        let color = merged
+            .result
+            .map_or(source.tint(), |result| catalog.kind(&result).tint());
+        let spread = merged
+            .result
+            .map_or(source.size, |result| catalog.kind(&result).size);
+        for _ in 0..PARTICLE_COUNT {
+            let direction = Vec2::from_angle(rng.gen_range(0.0..TAU));
+            let speed = rng.gen_range(0.6..1.4) * spread * 1.5;
+            commands.spawn((
+                MergeParticle {
+                    velocity: direction * speed,
+                    color,
+                    timer: Timer::from_seconds(PARTICLE_LIFETIME, TimerMode::Once),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(1.0, 1.0) * PARTICLE_SIZE),
+                        color,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        (merged.position + direction * spread / 4.0).extend(2.0),
+                    ),
+                    ..default()
+                },
+            ));
+        }
+
+        if let Some(entity) = merged.entity {
+            popping.timers.insert(
+                entity,
+                Timer::from_seconds(POP_IN_DURATION, TimerMode::Once),
+            );
+        }
+    }
+}
+
+pub fn animate_merge_tweens(
+    mut commands: Commands,
+    mut tweens: Query<(Entity, &mut MergeTween, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut tween, mut transform, mut sprite) in tweens.iter_mut() {
+        if tween.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = tween.timer.percent();
+        let position = tween.from.lerp(tween.to, t * t);
+        transform.translation = position.extend(transform.translation.z);
+        sprite.custom_size = Some(Vec2::new(1.0, 1.0) * tween.size * (1.0 - 0.3 * t));
+    }
+}
+
+pub fn animate_merge_particles(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut MergeParticle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let remaining = particle.timer.percent_left();
+        transform.translation += (particle.velocity * remaining * time.delta_seconds()).extend(0.0);
+        sprite.color = particle.color.with_a(remaining);
+        sprite.custom_size = Some(Vec2::new(1.0, 1.0) * PARTICLE_SIZE * (0.4 + 0.6 * remaining));
+    }
+}
+
+pub fn animate_pop_ins(
+    mut popping: ResMut<PoppingFruits>,
+    mut sprites: Query<&mut Sprite>,
+    time: Res<Time>,
+    catalog: Res<FruitCatalog>,
+    fruits: Query<&Fruit>,
+) {
+    popping.timers.retain(|entity, timer| {
+        let (Ok(mut sprite), Ok(fruit)) = (sprites.get_mut(*entity), fruits.get(*entity)) else {
+            // not drawn yet, or merged away already
+            return fruits.contains(*entity);
+        };
+        let finished = timer.tick(time.delta()).finished();
+        let size = catalog.kind(fruit).size;
+        sprite.custom_size = Some(Vec2::new(1.0, 1.0) * size * pop_in_scale(timer.percent()));
+        !finished
+    });
+}
+
+// grows past full size and settles back, like the original
+fn pop_in_scale(t: f32) -> f32 {
+    let overshoot = 1.70158;
+    let t = t - 1.0;
+    let eased = 1.0 + (overshoot + 1.0) * t * t * t + overshoot * t * t;
+    POP_IN_START_SCALE + (1.0 - POP_IN_START_SCALE) * eased
+}
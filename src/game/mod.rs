@@ -1,110 +1,200 @@
+use bevy::audio::Volume;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
 
+use crate::autoplay::AutoPlayer;
+use crate::catalog::FruitCatalog;
+use crate::constants::{SCORE_ROLL_MIN_SPEED, SCORE_ROLL_RATE};
+use crate::resources::{
+    Aim, GameMode, NoiseSetting, RecentTouch, ReducedMotion, RunClock, ScoreTracker,
+};
+use crate::setup::{ModeStatus, Score};
+use crate::simulation::{simulation_running, FruitDropped, FruitsMerged, MarkForDelete};
 use crate::{AppState, Fruit};
 
-use crate::constants::{CONTAINER_WIDTH, GRAVITY, RESTITUATION};
-
 mod mouse_click;
 use mouse_click::mouse_click;
 
-mod update_preview;
-use update_preview::update_preview;
+mod merge_effects;
+use merge_effects::{
+    animate_merge_particles, animate_merge_tweens, animate_pop_ins, spawn_merge_effects,
+    PoppingFruits,
+};
+
+mod popups;
+use popups::{animate_popups, spawn_combo_popups, spawn_score_popups};
 
-mod collision;
-use collision::{collision, remove_used_fruits};
+mod danger;
+use danger::update_danger_line;
 
-mod game_over;
-use game_over::check_game_over;
+mod keyboard;
+use keyboard::{keyboard_aim, keyboard_drop};
 
-mod physics_manipulations;
-use physics_manipulations::{clamp_upward_velocity, manipulate_mass, mark_fruits_as_alive};
+mod touch;
+use touch::{touch_controls, track_touches};
 
-use self::collision::merge_fruits;
-use self::physics_manipulations::change_manipulated_mass_on_slide;
+mod update_preview;
+use update_preview::{follow_mouse, move_preview, update_preview};
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                mouse_click,
-                update_preview,
-                collision,
-                clamp_upward_velocity,
-                check_game_over,
-                mark_fruits_as_alive.before(merge_fruits),
-                manipulate_mass.before(merge_fruits),
-                change_manipulated_mass_on_slide.before(merge_fruits),
-                merge_fruits.after(collision),
-                remove_used_fruits.after(merge_fruits),
+        app.init_resource::<Aim>()
+            .init_resource::<RecentTouch>()
+            .init_resource::<PoppingFruits>()
+            .add_systems(PreUpdate, track_touches.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    mouse_click,
+                    keyboard_drop,
+                    (follow_mouse, keyboard_aim, touch_controls)
+                        .chain()
+                        .in_set(AimInput),
+                    move_preview.after(AimInput),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(Update, update_preview.run_if(simulation_running))
+            .add_systems(
+                Update,
+                (
+                    play_fruit_sounds,
+                    update_score_text,
+                    update_mode_status,
+                    update_danger_line,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_merge_effects,
+                    spawn_score_popups,
+                    spawn_combo_popups,
+                    animate_merge_tweens,
+                    animate_merge_particles,
+                    animate_popups,
+                ),
             )
-                .run_if(in_state(AppState::InGame)),
-        );
+            // fruits spawned by the simulation are visible here
+            .add_systems(
+                PostUpdate,
+                (
+                    add_fruit_sprites,
+                    hide_merged_fruits,
+                    animate_pop_ins.after(add_fruit_sprites),
+                ),
+            );
     }
 }
 
-// tracks fruits that should be considered for game over conditions
-#[derive(Component)]
-pub struct Alive;
+// the preview follows the aim once these have run
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AimInput;
 
-#[derive(Component)]
-pub struct TimeSinceSpawn {
-    pub timer: Timer,
-}
-
-pub fn create_fruit_bundle(
-    texture_handle: Handle<Image>,
-    pos_x: f32,
-    pos_y: f32,
-    fruit: Fruit,
-) -> (
-    Fruit,
-    TimeSinceSpawn,
-    RigidBody,
-    SpriteBundle,
-    Collider,
-    GravityScale,
-    Restitution,
-    ActiveEvents,
-    Velocity,
+fn add_fruit_sprites(
+    mut commands: Commands,
+    new_fruits: Query<(Entity, &Fruit), Added<Fruit>>,
+    asset_server: Res<AssetServer>,
+    catalog: Res<FruitCatalog>,
+    popping: Res<PoppingFruits>,
 ) {
-    // make sure spawning position is in bounds
-    // adding one pixel on either edge to prevent collision against wall on drop
-    let size = fruit.size;
-    let pos_x_in_bounds = pos_x_in_bounds(pos_x, size);
-    (
-        fruit,
-        TimeSinceSpawn {
-            timer: Timer::from_seconds(1.0, TimerMode::Once),
-        },
-        RigidBody::Dynamic,
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(1.0, 1.0) * size),
+    for (entity, fruit) in new_fruits.iter() {
+        let kind = catalog.kind(fruit);
+        // merged fruits start small and pop in
+        let scale = popping.scale(entity).unwrap_or(1.0);
+        commands.entity(entity).insert((
+            Sprite {
+                custom_size: Some(Vec2::new(1.0, 1.0) * kind.size * scale),
                 ..default()
             },
-            texture: texture_handle,
-            transform: Transform::from_xyz(pos_x_in_bounds, pos_y, 0.0),
-            ..default()
-        },
-        Collider::ball(size / 2.0),
-        GravityScale(GRAVITY),
-        Restitution::coefficient(RESTITUATION),
-        ActiveEvents::COLLISION_EVENTS,
-        Velocity {
-            linvel: Vec2::new(0.0, 0.0),
-            angvel: 0.0,
-        },
-    )
+            asset_server.load::<Image, _>(&kind.texture),
+            Visibility::default(),
+            ComputedVisibility::default(),
+        ));
+    }
+}
+
+fn hide_merged_fruits(mut merged_fruits: Query<&mut Visibility, Added<MarkForDelete>>) {
+    for mut visibility in merged_fruits.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
 }
 
-pub fn pos_x_in_bounds(raw_x: f32, sprite_size: f32) -> f32 {
-    match raw_x {
-        x if x < 0.0 => x.max((-CONTAINER_WIDTH / 2.0 + sprite_size / 2.0) + 1.0),
-        x if x > 0.0 => x.min((CONTAINER_WIDTH / 2.0 - sprite_size / 2.0) - 1.0),
-        _ => raw_x,
+fn play_fruit_sounds(
+    mut commands: Commands,
+    mut fruit_dropped: EventReader<FruitDropped>,
+    mut fruits_merged: EventReader<FruitsMerged>,
+    asset_server: Res<AssetServer>,
+    noise_setting: Res<NoiseSetting>,
+    catalog: Res<FruitCatalog>,
+) {
+    let dropped = fruit_dropped.iter().map(|dropped| dropped.fruit);
+    let merged = fruits_merged.iter().filter_map(|merged| merged.result);
+    for fruit in dropped.chain(merged) {
+        if !noise_setting.is_on {
+            continue;
+        }
+        if let Some(sound) = &catalog.kind(&fruit).sound {
+            commands.spawn(AudioBundle {
+                source: asset_server.load(sound),
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(0.5)),
+            });
+        }
+    }
+}
+
+// a score going down (restarts, restored runs) is shown right away
+fn update_score_text(
+    score_tracker: Res<ScoreTracker>,
+    mut score_query: Query<&mut Text, With<Score>>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time>,
+    mut shown: Local<f32>,
+) {
+    let target = score_tracker.score as f32;
+    if target <= *shown || reduced_motion.is_on {
+        *shown = target;
+    } else {
+        let step = ((target - *shown) * SCORE_ROLL_RATE).max(SCORE_ROLL_MIN_SPEED);
+        *shown = (*shown + step * time.delta_seconds()).min(target);
+    }
+
+    if let Ok(mut score) = score_query.get_single_mut() {
+        let value = (*shown as u32).to_string();
+        if score.sections[0].value != value {
+            score.sections[0].value = value;
+        }
+    }
+}
+
+fn update_mode_status(
+    score_tracker: Res<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    autoplayer: Option<Res<AutoPlayer>>,
+    mut status_query: Query<&mut Text, With<ModeStatus>>,
+) {
+    let Ok(mut status) = status_query.get_single_mut() else {
+        return;
+    };
+    let mut value = match score_tracker.mode {
+        GameMode::TimeAttack => {
+            let limit = score_tracker.mode.time_limit().unwrap_or_default();
+            let left = (limit - run_clock.elapsed).max(0.0).ceil() as u32;
+            format!("{}:{:02} left", left / 60, left % 60)
+        }
+        GameMode::Target => format!("{} drops", score_tracker.drops),
+        GameMode::Daily => String::from("daily challenge"),
+        GameMode::Classic | GameMode::Zen => String::new(),
+    };
+    if let Some(autoplayer) = autoplayer.filter(|autoplayer| autoplayer.enabled) {
+        if !value.is_empty() {
+            value.push_str(", ");
+        }
+        value.push_str(&format!("autoplay: {}", autoplayer.strategy.name()));
+    }
+    if status.sections[0].value != value {
+        status.sections[0].value = value;
     }
 }
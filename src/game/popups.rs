@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::constants::{COMBO_COLOR, SCORE_TEXT_COLOR};
+use crate::resources::{ReducedMotion, ScoreTracker, ScoringRules};
+use crate::simulation::FruitsMerged;
+
+const POPUP_DURATION: f32 = 0.8;
+// pixels a popup floats up over its lifetime
+const POPUP_RISE: f32 = 40.0;
+
+#[derive(Component)]
+pub struct Popup {
+    start: Vec2,
+    color: Color,
+    timer: Timer,
+}
+
+impl Popup {
+    fn new(start: Vec2, color: Color) -> Self {
+        Self {
+            start,
+            color,
+            timer: Timer::from_seconds(POPUP_DURATION, TimerMode::Once),
+        }
+    }
+}
+
+fn popup_bundle(popup: &Popup, value: String, font_size: f32) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font_size,
+                color: popup.color,
+                ..default()
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        transform: Transform::from_translation(popup.start.extend(3.0)),
+        ..default()
+    }
+}
+
+pub fn spawn_score_popups(mut commands: Commands, mut fruits_merged: EventReader<FruitsMerged>) {
+    for merged in fruits_merged.iter() {
+        // two of the largest fruits merging is worth nothing
+        if merged.points == 0 {
+            continue;
+        }
+        let popup = Popup::new(merged.position, SCORE_TEXT_COLOR);
+        let bundle = popup_bundle(&popup, format!("+{}", merged.points), 24.0);
+        commands.spawn((popup, bundle));
+    }
+}
+
+pub fn spawn_combo_popups(
+    mut commands: Commands,
+    mut fruits_merged: EventReader<FruitsMerged>,
+    score_tracker: Res<ScoreTracker>,
+) {
+    for merged in fruits_merged.iter() {
+        // versus boards always score by the classic rules
+        if score_tracker.rules != ScoringRules::Combo || merged.chain < 2 || merged.side.is_some() {
+            continue;
+        }
+        let popup = Popup::new(merged.position + Vec2::new(0.0, 30.0), COMBO_COLOR);
+        let bundle = popup_bundle(&popup, format!("x{} combo", merged.chain), 28.0);
+        commands.spawn((popup, bundle));
+    }
+}
+
+pub fn animate_popups(
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        if popup.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = popup.timer.percent();
+        // with reduced motion popups stay put and only fade
+        if !reduced_motion.is_on {
+            let position = popup.start + Vec2::new(0.0, POPUP_RISE * t);
+            transform.translation = position.extend(transform.translation.z);
+        }
+        text.sections[0].style.color = popup.color.with_a(1.0 - t * t);
+    }
+}
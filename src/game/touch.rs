@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::helpers::{pointer_over_ui, screen_to_world};
+use crate::layout::Layout;
+use crate::resources::{Aim, RecentTouch};
+use crate::setup::MainCamera;
+use crate::simulation::{DropFruit, HoldFruit};
+
+pub fn track_touches(
+    touches: Res<Touches>,
+    time: Res<Time>,
+    mut recent_touch: ResMut<RecentTouch>,
+) {
+    if touches.iter().next().is_some() || touches.iter_just_released().next().is_some() {
+        recent_touch.touched();
+    } else {
+        recent_touch.timer.tick(time.delta());
+    }
+}
+
+// only touches that began on the board in game count, not ones on a control or the tap that left the menu
+#[allow(clippy::too_many_arguments)]
+pub fn touch_controls(
+    touches: Res<Touches>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    ui_interactions: Query<&Interaction, With<Node>>,
+    layout: Res<Layout>,
+    mut aim: ResMut<Aim>,
+    mut hold_requests: EventWriter<HoldFruit>,
+    mut drop_requests: EventWriter<DropFruit>,
+    mut board_touches: Local<HashSet<u64>>,
+) {
+    // bevy_ui has already marked whatever control a new touch landed on as pressed
+    if !pointer_over_ui(&ui_interactions) {
+        for touch in touches.iter_just_pressed() {
+            let on_hold = screen_to_world(&camera_q, touch.position())
+                .is_some_and(|position| layout.in_hold_panel(position));
+            if on_hold {
+                hold_requests.send(HoldFruit);
+            } else {
+                board_touches.insert(touch.id());
+            }
+        }
+    }
+
+    for touch in touches.iter() {
+        if !board_touches.contains(&touch.id()) {
+            continue;
+        }
+        if let Some(world_position) = screen_to_world(&camera_q, touch.position()) {
+            aim.x = world_position.x;
+        }
+    }
+
+    for touch in touches.iter_just_released() {
+        if board_touches.remove(&touch.id()) {
+            if let Some(world_position) = screen_to_world(&camera_q, touch.position()) {
+                drop_requests.send(DropFruit {
+                    x: world_position.x,
+                });
+            }
+        }
+    }
+
+    // cancelled touches and ones that ended outside the game don't drop
+    board_touches.retain(|id| touches.get_pressed(*id).is_some());
+}
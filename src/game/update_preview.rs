@@ -1,40 +1,87 @@
+use crate::catalog::FruitCatalog;
+use crate::constants::HOLD_USED_COLOR;
 use crate::helpers::get_mouse_pos;
-use crate::resources::NextGenerator;
-use crate::setup::{MainCamera, NextPreview, Preview};
+use crate::resources::{Aim, NextGenerator};
+use crate::setup::{HoldPreview, MainCamera, NextPreview, Preview};
+use crate::simulation::pos_x_in_bounds;
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use super::pos_x_in_bounds;
-
-pub fn update_preview(
+// only a moving cursor takes over the aim, so a resting mouse doesn't fight the keyboard
+pub fn follow_mouse(
+    mut cursor_moved: EventReader<CursorMoved>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut preview: Query<(&Preview, &mut Sprite, &mut Handle<Image>, &mut Transform)>,
+    mut aim: ResMut<Aim>,
+) {
+    if cursor_moved.iter().last().is_none() {
+        return;
+    }
+
+    if let Some(world_position) = get_mouse_pos(&q_windows, &camera_q) {
+        aim.x = world_position[0];
+    }
+}
+
+pub fn move_preview(
+    mut preview: Query<&mut Transform, With<Preview>>,
+    aim: Res<Aim>,
+    next_generator: Res<NextGenerator>,
+    catalog: Res<FruitCatalog>,
+) {
+    if let Ok(mut transform) = preview.get_single_mut() {
+        transform.translation.x =
+            pos_x_in_bounds(aim.x, catalog.kind(&next_generator.current_fruit).size);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_preview(
+    mut preview: Query<(&Preview, &mut Sprite, &mut Handle<Image>)>,
     mut next_preview: Query<(&NextPreview, &mut Sprite, &mut Handle<Image>), Without<Preview>>,
+    mut hold_preview: Query<
+        (&mut Sprite, &mut Handle<Image>, &mut Visibility),
+        (With<HoldPreview>, Without<Preview>, Without<NextPreview>),
+    >,
     asset_server: Res<AssetServer>,
     mut next_generator: ResMut<NextGenerator>,
+    catalog: Res<FruitCatalog>,
 ) {
-    let mouse_pos = get_mouse_pos(q_windows, camera_q);
-
-    if let Some(world_position) = mouse_pos {
-        let pos = pos_x_in_bounds(world_position[0], next_generator.current_fruit.size);
+    // if preview images and sizes need to be updated
+    if !next_generator.should_update_previews {
+        return;
+    }
+    if let (Ok((_, mut sprite, mut handle)), Ok((_, mut next_sprite, mut next_handle))) =
+        (preview.get_single_mut(), next_preview.get_single_mut())
+    {
         // update current preview
-        let (_, mut sprite, mut handle, mut transform) = preview.single_mut();
-        transform.translation.x = pos;
+        let current = catalog.kind(&next_generator.current_fruit);
+        sprite.custom_size = Some(Vec2::new(1.0, 1.0) * current.size);
+        *handle = asset_server.load(&current.texture);
 
-        // if preview images and sizes need to be updated
-        if next_generator.should_update_previews {
-            sprite.custom_size = Some(Vec2::new(1.0, 1.0) * next_generator.current_fruit.size);
-            let texture_handle =
-                asset_server.load(format!("{}.png", &next_generator.current_fruit.file_name));
-            *handle = texture_handle;
+        // update next preview
+        let next = catalog.kind(&next_generator.next_fruit);
+        next_sprite.custom_size = Some(Vec2::new(1.0, 1.0) * next.size);
+        *next_handle = asset_server.load(&next.texture);
 
-            // update next preview
-            let (_, mut next_sprite, mut next_handle) = next_preview.single_mut();
-            next_sprite.custom_size = Some(Vec2::new(1.0, 1.0) * next_generator.next_fruit.size);
-            let next_texture_handle =
-                asset_server.load(format!("{}.png", &next_generator.next_fruit.file_name));
-            *next_handle = next_texture_handle;
-            next_generator.preview_updated();
+        // update hold preview, faded while it can't be used
+        if let Ok((mut hold_sprite, mut hold_handle, mut visibility)) =
+            hold_preview.get_single_mut()
+        {
+            match next_generator.held_fruit {
+                Some(held) => {
+                    let held = catalog.kind(&held);
+                    hold_sprite.custom_size = Some(Vec2::new(1.0, 1.0) * held.size);
+                    hold_sprite.color = if next_generator.hold_used {
+                        HOLD_USED_COLOR
+                    } else {
+                        Color::WHITE
+                    };
+                    *hold_handle = asset_server.load(&held.texture);
+                    *visibility = Visibility::Inherited;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
         }
+        next_generator.preview_updated();
     }
 }
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{AIM_COARSE_SPEED, AIM_FINE_SPEED};
+use crate::game::AimInput;
+use crate::resources::{Aim, MassSetting, NextGenerator};
+use crate::simulation::{pos_x_in_bounds, DropFruit, HoldFruit};
+use crate::AppState;
+
+// stick values below this are treated as resting
+pub const STICK_DEADZONE: f32 = 0.15;
+// how far a fully pulled trigger moves the physics slider per second
+const TRIGGER_SLIDE_SPEED: f32 = 0.5;
+
+pub struct GamepadInputPlugin;
+
+impl Plugin for GamepadInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                gamepad_aim.in_set(AimInput),
+                gamepad_drop,
+                gamepad_physics_slider,
+            )
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+pub fn any_gamepad_just_pressed(
+    gamepads: &Gamepads,
+    button_input: &Input<GamepadButton>,
+    button_types: impl IntoIterator<Item = GamepadButtonType> + Clone,
+) -> bool {
+    gamepads.iter().any(|gamepad| {
+        button_types
+            .clone()
+            .into_iter()
+            .any(|button_type| button_input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    })
+}
+
+fn gamepad_aim(
+    gamepads: Res<Gamepads>,
+    button_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    mut aim: ResMut<Aim>,
+    next_generator: Res<NextGenerator>,
+    catalog: Res<FruitCatalog>,
+) {
+    let mut movement = 0.0;
+    for gamepad in gamepads.iter() {
+        let stick = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.0);
+        if stick.abs() > STICK_DEADZONE {
+            movement += stick * AIM_COARSE_SPEED;
+        }
+        if button_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft)) {
+            movement -= AIM_FINE_SPEED;
+        }
+        if button_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadRight)) {
+            movement += AIM_FINE_SPEED;
+        }
+    }
+    if movement == 0.0 {
+        return;
+    }
+
+    aim.x = pos_x_in_bounds(
+        aim.x + movement * time.delta_seconds(),
+        catalog.kind(&next_generator.current_fruit).size,
+    );
+}
+
+fn gamepad_drop(
+    gamepads: Res<Gamepads>,
+    button_input: Res<Input<GamepadButton>>,
+    aim: Res<Aim>,
+    mut hold_requests: EventWriter<HoldFruit>,
+    mut drop_requests: EventWriter<DropFruit>,
+) {
+    if any_gamepad_just_pressed(&gamepads, &button_input, [GamepadButtonType::West]) {
+        hold_requests.send(HoldFruit);
+    }
+    if any_gamepad_just_pressed(&gamepads, &button_input, [GamepadButtonType::South]) {
+        drop_requests.send(DropFruit { x: aim.x });
+    }
+}
+
+// right trigger slides towards "crazy", left trigger back towards "stable"
+fn gamepad_physics_slider(
+    gamepads: Res<Gamepads>,
+    button_axes: Res<Axis<GamepadButton>>,
+    time: Res<Time>,
+    mut mass_setting: ResMut<MassSetting>,
+) {
+    let mut change = 0.0;
+    for gamepad in gamepads.iter() {
+        let trigger = |button_type| {
+            button_axes
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or(0.0)
+        };
+        change += trigger(GamepadButtonType::LeftTrigger2);
+        change -= trigger(GamepadButtonType::RightTrigger2);
+    }
+    if change == 0.0 {
+        return;
+    }
+
+    mass_setting.percentage = (mass_setting.percentage
+        + change * TRIGGER_SLIDE_SPEED * time.delta_seconds())
+    .clamp(0.0, 1.0);
+}
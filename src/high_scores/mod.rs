@@ -0,0 +1,445 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{SCORE_TEXT_COLOR, SCREEN_HEIGHT, TEXT_COLOR};
+use crate::menu::MenuItem;
+use crate::resources::{GameMode, GameRng, RunClock, ScoreTracker, ScoringRules};
+use crate::simulation::{mode_finished, GameOver, RestartGame, SimulationSet};
+use crate::storage;
+use crate::AppState;
+
+const STORAGE_KEY: &str = "high_scores";
+// per mode
+const MAX_ENTRIES: usize = 10;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<LatestRank>()
+            // the start menu comes back after a versus match
+            .add_systems(OnEnter(AppState::StartMenu), setup_start_menu_scores)
+            .add_systems(Update, record_high_score.run_if(in_state(AppState::InGame)))
+            // zen runs only end by restarting or quitting, before the simulation clears the score
+            .add_systems(
+                FixedUpdate,
+                record_zen_run
+                    .before(SimulationSet)
+                    .run_if(in_state(AppState::InGame).or_else(in_state(AppState::Paused))),
+            )
+            .add_systems(
+                Last,
+                record_zen_run_on_exit
+                    .run_if(in_state(AppState::InGame).or_else(in_state(AppState::Paused))),
+            )
+            .add_systems(OnEnter(AppState::GameOverMenu), setup_game_over_scores)
+            .add_systems(
+                Update,
+                refresh_score_table
+                    .run_if(resource_changed::<GameMode>())
+                    .run_if(
+                        in_state(AppState::StartMenu).or_else(in_state(AppState::GameOverMenu)),
+                    ),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScore {
+    pub score: u32,
+    // unix seconds
+    pub date: u64,
+    pub seed: u64,
+    pub largest_tier: usize,
+    // seconds
+    pub play_time: f32,
+    pub rules: ScoringRules,
+    // longest chain and number of merges that were part of one, always 0 with classic rules
+    pub best_combo: u32,
+    pub combo_merges: u32,
+    pub mode: GameMode,
+    pub drops: u32,
+}
+
+impl HighScore {
+    fn beats(&self, other: &HighScore) -> bool {
+        if self.mode.ranks_by_drops() && self.drops != other.drops {
+            return self.drops < other.drops;
+        }
+        self.score > other.score
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn for_mode(&self, mode: GameMode) -> impl Iterator<Item = &HighScore> {
+        self.entries.iter().filter(move |entry| entry.mode == mode)
+    }
+
+    // the rank within its mode, if it made that mode's table
+    pub fn submit(&mut self, high_score: HighScore) -> Option<usize> {
+        let mode = high_score.mode;
+        let rank = self
+            .for_mode(mode)
+            .position(|entry| high_score.beats(entry))
+            .unwrap_or(self.for_mode(mode).count());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        // the order only matters within a mode
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.mode == mode && high_score.beats(entry))
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, high_score);
+        if let Some(last) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.mode == mode)
+            .nth(MAX_ENTRIES)
+            .map(|(index, _)| index)
+        {
+            self.entries.remove(last);
+        }
+        Some(rank)
+    }
+}
+
+// where the last finished run landed in its mode's table, if it did
+#[derive(Resource, Default)]
+struct LatestRank {
+    rank: Option<(GameMode, usize)>,
+}
+
+fn current_run(
+    score_tracker: &ScoreTracker,
+    run_clock: &RunClock,
+    game_rng: &GameRng,
+) -> HighScore {
+    HighScore {
+        score: score_tracker.score,
+        date: storage::now_unix_secs(),
+        seed: game_rng.seed,
+        largest_tier: score_tracker.largest_tier,
+        play_time: run_clock.elapsed,
+        rules: score_tracker.rules,
+        best_combo: score_tracker.best_combo,
+        combo_merges: score_tracker.combo_merges,
+        mode: score_tracker.mode,
+        drops: score_tracker.drops,
+    }
+}
+
+fn record_high_score(
+    mut game_over: EventReader<GameOver>,
+    score_tracker: Res<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    game_rng: Res<GameRng>,
+    catalog: Res<FruitCatalog>,
+    mut high_scores: ResMut<HighScores>,
+    mut latest_rank: ResMut<LatestRank>,
+) {
+    for _ in game_over.iter() {
+        latest_rank.rank = None;
+        if score_tracker.autoplayed {
+            continue;
+        }
+        if score_tracker.mode == GameMode::Target
+            && !mode_finished(&score_tracker, &run_clock, &catalog)
+        {
+            continue;
+        }
+        let high_score = current_run(&score_tracker, &run_clock, &game_rng);
+        latest_rank.rank = high_scores
+            .submit(high_score)
+            .map(|rank| (score_tracker.mode, rank));
+        high_scores.save();
+    }
+}
+
+fn record_zen_run(
+    mut restart_requests: EventReader<RestartGame>,
+    mut score_tracker: ResMut<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    game_rng: Res<GameRng>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if restart_requests.iter().count() > 0 {
+        submit_zen_run(&mut score_tracker, &run_clock, &game_rng, &mut high_scores);
+    }
+}
+
+// closing the window sends AppExit too
+fn record_zen_run_on_exit(
+    mut app_exit: EventReader<AppExit>,
+    mut score_tracker: ResMut<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    game_rng: Res<GameRng>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if app_exit.iter().count() > 0 {
+        submit_zen_run(&mut score_tracker, &run_clock, &game_rng, &mut high_scores);
+    }
+}
+
+fn submit_zen_run(
+    score_tracker: &mut ScoreTracker,
+    run_clock: &RunClock,
+    game_rng: &GameRng,
+    high_scores: &mut HighScores,
+) {
+    if score_tracker.mode != GameMode::Zen
+        || score_tracker.drops == 0
+        || score_tracker.autoplayed
+        || score_tracker.recorded
+    {
+        return;
+    }
+    score_tracker.recorded = true;
+    high_scores.submit(current_run(score_tracker, run_clock, game_rng));
+    high_scores.save();
+}
+
+#[derive(Component)]
+struct ScoreTable;
+
+const START_MENU_TABLE_TOP: f32 = -100.0;
+const GAME_OVER_TABLE_TOP: f32 = SCREEN_HEIGHT / 2.0 - 320.0;
+
+fn setup_start_menu_scores(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    catalog: Res<FruitCatalog>,
+    game_mode: Res<GameMode>,
+) {
+    spawn_table(
+        &mut commands,
+        &high_scores,
+        &catalog,
+        *game_mode,
+        None,
+        START_MENU_TABLE_TOP,
+    );
+}
+
+fn setup_game_over_scores(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    catalog: Res<FruitCatalog>,
+    game_mode: Res<GameMode>,
+    latest_rank: Res<LatestRank>,
+) {
+    spawn_table(
+        &mut commands,
+        &high_scores,
+        &catalog,
+        *game_mode,
+        highlighted(&latest_rank, *game_mode),
+        GAME_OVER_TABLE_TOP,
+    );
+}
+
+fn refresh_score_table(
+    mut commands: Commands,
+    tables: Query<Entity, With<ScoreTable>>,
+    high_scores: Res<HighScores>,
+    catalog: Res<FruitCatalog>,
+    game_mode: Res<GameMode>,
+    latest_rank: Res<LatestRank>,
+    state: Res<State<AppState>>,
+) {
+    for table in tables.iter() {
+        commands.entity(table).despawn_recursive();
+    }
+    let (top, highlight) = match state.get() {
+        AppState::GameOverMenu => (GAME_OVER_TABLE_TOP, highlighted(&latest_rank, *game_mode)),
+        _ => (START_MENU_TABLE_TOP, None),
+    };
+    spawn_table(
+        &mut commands,
+        &high_scores,
+        &catalog,
+        *game_mode,
+        highlight,
+        top,
+    );
+}
+
+fn highlighted(latest_rank: &LatestRank, mode: GameMode) -> Option<usize> {
+    latest_rank
+        .rank
+        .filter(|(ranked_mode, _)| *ranked_mode == mode)
+        .map(|(_, rank)| rank)
+}
+
+fn spawn_table(
+    commands: &mut Commands,
+    high_scores: &HighScores,
+    catalog: &FruitCatalog,
+    mode: GameMode,
+    highlight: Option<usize>,
+    top: f32,
+) {
+    if high_scores.for_mode(mode).next().is_none() {
+        return;
+    }
+    let style = |color: Color| TextStyle {
+        font_size: 18.0,
+        color,
+        ..default()
+    };
+    let mut sections = vec![TextSection::new(
+        format!("high scores - {}\n", mode.name()),
+        style(TEXT_COLOR),
+    )];
+    for (rank, entry) in high_scores.for_mode(mode).enumerate() {
+        let largest = catalog
+            .fruits
+            .get(entry.largest_tier)
+            .map_or("?", |kind| kind.name.as_str());
+        let color = if highlight == Some(rank) {
+            SCORE_TEXT_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        let combo = match entry.rules {
+            ScoringRules::Classic => String::new(),
+            ScoringRules::Combo => format!("  best combo x{}", entry.best_combo),
+        };
+        // fewest drops is what counts in target mode
+        let result = if mode.ranks_by_drops() {
+            format!("{} drops  {}", entry.drops, entry.score)
+        } else {
+            entry.score.to_string()
+        };
+        sections.push(TextSection::new(
+            format!(
+                "{}. {}  {}  {}:{:02}  {}  seed {}{}\n",
+                rank + 1,
+                result,
+                largest,
+                entry.play_time as u32 / 60,
+                entry.play_time as u32 % 60,
+                storage::format_date(entry.date),
+                entry.seed,
+                combo
+            ),
+            style(color),
+        ));
+    }
+    commands.spawn((
+        MenuItem,
+        ScoreTable,
+        Text2dBundle {
+            text: Text::from_sections(sections).with_alignment(TextAlignment::Center),
+            text_anchor: Anchor::TopCenter,
+            transform: Transform::from_xyz(0.0, top, 1.0),
+            ..default()
+        },
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(mode: GameMode, score: u32, drops: u32) -> HighScore {
+        HighScore {
+            score,
+            date: 0,
+            seed: 0,
+            largest_tier: 0,
+            play_time: 0.0,
+            rules: ScoringRules::default(),
+            best_combo: 0,
+            combo_merges: 0,
+            mode,
+            drops,
+        }
+    }
+
+    fn scores(high_scores: &HighScores, mode: GameMode) -> Vec<u32> {
+        high_scores
+            .for_mode(mode)
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn runs_are_ranked_best_first() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 100, 0)), Some(0));
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 300, 0)), Some(0));
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 200, 0)), Some(1));
+        // a tie goes below the run that got there first
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 200, 0)), Some(2));
+        assert_eq!(
+            scores(&high_scores, GameMode::Classic),
+            [300, 200, 200, 100]
+        );
+    }
+
+    #[test]
+    fn each_mode_has_its_own_table() {
+        let mut high_scores = HighScores::default();
+        high_scores.submit(run(GameMode::Classic, 100, 0));
+        assert_eq!(high_scores.submit(run(GameMode::Zen, 50, 0)), Some(0));
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 50, 0)), Some(1));
+        assert_eq!(scores(&high_scores, GameMode::Classic), [100, 50]);
+        assert_eq!(scores(&high_scores, GameMode::Zen), [50]);
+    }
+
+    #[test]
+    fn target_runs_rank_by_fewest_drops() {
+        let mut high_scores = HighScores::default();
+        high_scores.submit(run(GameMode::Target, 500, 40));
+        assert_eq!(high_scores.submit(run(GameMode::Target, 100, 30)), Some(0));
+        // the score only breaks ties
+        assert_eq!(high_scores.submit(run(GameMode::Target, 600, 40)), Some(1));
+        assert_eq!(scores(&high_scores, GameMode::Target), [100, 600, 500]);
+    }
+
+    #[test]
+    fn table_keeps_the_best_runs_only() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_ENTRIES as u32 {
+            assert!(high_scores
+                .submit(run(GameMode::Classic, score * 10, 0))
+                .is_some());
+        }
+        // other modes don't count towards the limit
+        high_scores.submit(run(GameMode::Zen, 1, 0));
+
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 5, 0)), None);
+        assert_eq!(high_scores.submit(run(GameMode::Classic, 10, 0)), None);
+        assert_eq!(high_scores.for_mode(GameMode::Classic).count(), MAX_ENTRIES);
+
+        assert_eq!(
+            high_scores.submit(run(GameMode::Classic, 15, 0)),
+            Some(MAX_ENTRIES - 1)
+        );
+        let classic = scores(&high_scores, GameMode::Classic);
+        assert_eq!(classic.len(), MAX_ENTRIES);
+        assert_eq!(classic.last(), Some(&15));
+        assert_eq!(classic.first(), Some(&(MAX_ENTRIES as u32 * 10)));
+        assert_eq!(scores(&high_scores, GameMode::Zen), [1]);
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::constants::{
+    CONTAINER_BASE_OFFSET, CONTAINER_HEIGHT, CONTAINER_WIDTH, CONTROLS_BOTTOM, CONTROLS_GAP,
+    CONTROLS_RIGHT, CONTROL_HEIGHT, CONTROL_WIDTH, HOLD_PANEL_DROP, MENU_CONTROLS_GAP,
+    MENU_CONTROLS_TOP, NEXT_PREVIEW_FRUIT_SIZE, NEXT_PREVIEW_LABEL_SIZE, NEXT_PREVIEW_OFFSET,
+    PORTRAIT_BOTTOM_ROW_HEIGHT, PORTRAIT_CONTROLS_HEIGHT, PORTRAIT_CONTROLS_WIDTH,
+    PORTRAIT_PANEL_OFFSET, PORTRAIT_TOP_ROW_HEIGHT, PORTRAIT_WIDTH, PREVIEW_PANEL_HEIGHT,
+    PREVIEW_PANEL_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+use crate::menu::ControlsPanel;
+use crate::setup::{HoldPanel, MainCamera, MergeGuideEntry, NextPanel, ScorePanel};
+use crate::AppState;
+
+// the board never moves, only the camera and the panels around it
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Layout>()
+            .add_systems(Update, update_layout)
+            .add_systems(
+                Update,
+                (
+                    // the versus arena fits the camera itself
+                    apply_camera_layout.run_if(not(in_state(AppState::Versus))),
+                    apply_controls_layout,
+                    apply_panel_layout,
+                    apply_merge_guide_layout,
+                )
+                    .after(update_layout)
+                    .run_if(resource_changed::<Layout>()),
+            )
+            .add_systems(OnExit(AppState::Versus), apply_camera_layout);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Layout {
+    pub orientation: Orientation,
+    // logical window pixels per world unit
+    pub pixels_per_unit: f32,
+    pub ui_scale: f32,
+    pub camera_y: f32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::compute(SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+}
+
+impl Layout {
+    pub fn compute(window_width: f32, window_height: f32) -> Self {
+        if window_width >= window_height {
+            let pixels_per_unit = (window_width / SCREEN_WIDTH).min(window_height / SCREEN_HEIGHT);
+            return Self {
+                orientation: Orientation::Landscape,
+                pixels_per_unit,
+                ui_scale: pixels_per_unit,
+                camera_y: 0.0,
+            };
+        }
+
+        // the controls take a strip at the bottom of the window, the world gets the rest
+        let portrait_top = SCREEN_HEIGHT / 2.0 + PORTRAIT_TOP_ROW_HEIGHT;
+        let portrait_bottom = -SCREEN_HEIGHT / 2.0 - PORTRAIT_BOTTOM_ROW_HEIGHT;
+        let portrait_height = portrait_top - portrait_bottom;
+        let ui_scale = (window_width / PORTRAIT_CONTROLS_WIDTH)
+            .min(window_height / (portrait_height + PORTRAIT_CONTROLS_HEIGHT));
+        let controls_height = PORTRAIT_CONTROLS_HEIGHT * ui_scale;
+        let pixels_per_unit = (window_width / PORTRAIT_WIDTH)
+            .min((window_height - controls_height) / portrait_height);
+        Self {
+            orientation: Orientation::Portrait,
+            pixels_per_unit,
+            ui_scale,
+            camera_y: (portrait_top + portrait_bottom) / 2.0
+                - controls_height / pixels_per_unit / 2.0,
+        }
+    }
+
+    pub fn next_panel(&self) -> Vec2 {
+        match self.orientation {
+            Orientation::Landscape => Vec2::new(
+                CONTAINER_WIDTH / 2.0 + NEXT_PREVIEW_OFFSET,
+                CONTAINER_HEIGHT / 2.0,
+            ),
+            Orientation::Portrait => Vec2::new(
+                PORTRAIT_PANEL_OFFSET,
+                SCREEN_HEIGHT / 2.0 + PORTRAIT_TOP_ROW_HEIGHT / 2.0 - NEXT_PREVIEW_LABEL_SIZE / 2.0,
+            ),
+        }
+    }
+
+    pub fn hold_panel(&self) -> Vec2 {
+        match self.orientation {
+            Orientation::Landscape => Vec2::new(
+                -CONTAINER_WIDTH / 2.0 - NEXT_PREVIEW_OFFSET,
+                CONTAINER_HEIGHT / 2.0 - HOLD_PANEL_DROP,
+            ),
+            Orientation::Portrait => Vec2::new(-PORTRAIT_PANEL_OFFSET, self.next_panel().y),
+        }
+    }
+
+    pub fn in_hold_panel(&self, position: Vec2) -> bool {
+        let center = self.hold_panel() + Vec2::new(0.0, NEXT_PREVIEW_LABEL_SIZE / 2.0);
+        let offset = (position - center).abs();
+        offset.x <= PREVIEW_PANEL_WIDTH / 2.0 && offset.y <= PREVIEW_PANEL_HEIGHT / 2.0
+    }
+
+    // above the hold preview in landscape, between the two previews in portrait
+    pub fn score_panel(&self) -> Vec2 {
+        let label_height = NEXT_PREVIEW_FRUIT_SIZE / 2.0 + NEXT_PREVIEW_LABEL_SIZE / 2.0;
+        match self.orientation {
+            Orientation::Landscape => Vec2::new(
+                -CONTAINER_WIDTH / 2.0 - NEXT_PREVIEW_OFFSET,
+                CONTAINER_HEIGHT / 2.0 + label_height,
+            ),
+            Orientation::Portrait => Vec2::new(0.0, self.next_panel().y + label_height),
+        }
+    }
+
+    // a column next to the board in landscape, a row under it in portrait
+    pub fn merge_guide(&self, sizes: &[f32]) -> (Vec<Vec2>, f32) {
+        const SPACING: f32 = 10.0;
+        let base = -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET;
+        match self.orientation {
+            Orientation::Landscape => {
+                let mut offset: f32 = 0.0;
+                let positions = sizes
+                    .iter()
+                    .map(|size| {
+                        let position = Vec2::new(-SCREEN_WIDTH / 2.0 + 60.0, base + offset);
+                        offset += size + SPACING;
+                        position
+                    })
+                    .collect();
+                (positions, 1.0)
+            }
+            Orientation::Portrait => {
+                let total = sizes.iter().map(|size| size + SPACING).sum::<f32>() - SPACING;
+                let scale = ((PORTRAIT_WIDTH - 2.0 * SPACING) / total).min(1.0);
+                let mut x = -total * scale / 2.0;
+                let y = -SCREEN_HEIGHT / 2.0 - PORTRAIT_BOTTOM_ROW_HEIGHT / 2.0;
+                let positions = sizes
+                    .iter()
+                    .map(|size| {
+                        let position = Vec2::new(x + size * scale / 2.0, y);
+                        x += (size + SPACING) * scale;
+                        position
+                    })
+                    .collect();
+                (positions, scale)
+            }
+        }
+    }
+
+    // the mode button is the first
+    pub fn menu_control_top(slot: usize) -> f32 {
+        MENU_CONTROLS_TOP + slot as f32 * (CONTROL_HEIGHT + MENU_CONTROLS_GAP)
+    }
+
+    pub fn controls_style(&self) -> Style {
+        match self.orientation {
+            Orientation::Landscape => Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(CONTROLS_RIGHT),
+                bottom: Val::Px(CONTROLS_BOTTOM),
+                width: Val::Px(CONTROL_WIDTH),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(CONTROLS_GAP),
+                ..default()
+            },
+            Orientation::Portrait => Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(10.0),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::SpaceEvenly,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+        }
+    }
+}
+
+fn update_layout(
+    mut window_resized: EventReader<WindowResized>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    mut layout: ResMut<Layout>,
+    mut initialized: Local<bool>,
+) {
+    // the window may differ from the default size before any resize event
+    if window_resized.iter().last().is_none() && *initialized {
+        return;
+    }
+    let Ok(window) = q_windows.get_single() else {
+        return;
+    };
+    // minimized windows report a zero size, keep the last layout until they come back
+    if window.width() <= 0.0 || window.height() <= 0.0 {
+        return;
+    }
+    *initialized = true;
+    let new_layout = Layout::compute(window.width(), window.height());
+    if *layout != new_layout {
+        *layout = new_layout;
+    }
+}
+
+fn apply_camera_layout(
+    layout: Res<Layout>,
+    mut camera: Query<(&mut OrthographicProjection, &mut Transform), With<MainCamera>>,
+) {
+    if let Ok((mut projection, mut transform)) = camera.get_single_mut() {
+        projection.scaling_mode = ScalingMode::WindowSize(layout.pixels_per_unit);
+        transform.translation.x = 0.0;
+        transform.translation.y = layout.camera_y;
+    }
+}
+
+fn apply_controls_layout(
+    layout: Res<Layout>,
+    mut ui_scale: ResMut<UiScale>,
+    mut panel: Query<&mut Style, With<ControlsPanel>>,
+) {
+    ui_scale.scale = layout.ui_scale as f64;
+    if let Ok(mut style) = panel.get_single_mut() {
+        *style = layout.controls_style();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_panel_layout(
+    layout: Res<Layout>,
+    mut next_panel: Query<&mut Transform, (With<NextPanel>, Without<ScorePanel>)>,
+    mut hold_panel: Query<
+        &mut Transform,
+        (With<HoldPanel>, Without<NextPanel>, Without<ScorePanel>),
+    >,
+    mut score_panel: Query<&mut Transform, With<ScorePanel>>,
+) {
+    if let Ok(mut transform) = next_panel.get_single_mut() {
+        transform.translation = layout.next_panel().extend(transform.translation.z);
+    }
+    if let Ok(mut transform) = hold_panel.get_single_mut() {
+        transform.translation = layout.hold_panel().extend(transform.translation.z);
+    }
+    if let Ok(mut transform) = score_panel.get_single_mut() {
+        transform.translation = layout.score_panel().extend(transform.translation.z);
+    }
+}
+
+fn apply_merge_guide_layout(
+    layout: Res<Layout>,
+    mut entries: Query<(&MergeGuideEntry, &mut Transform)>,
+) {
+    let mut entries: Vec<_> = entries.iter_mut().collect();
+    entries.sort_by_key(|(entry, _)| entry.index);
+    let sizes: Vec<f32> = entries.iter().map(|(entry, _)| entry.size).collect();
+    let (positions, scale) = layout.merge_guide(&sizes);
+    for ((_, transform), position) in entries.iter_mut().zip(positions) {
+        transform.translation = position.extend(transform.translation.z);
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+pub mod autoplay;
+pub mod catalog;
+use catalog::FruitCatalog;
+
+pub mod clipboard;
+pub mod constants;
+pub mod daily;
+pub mod env;
+pub mod game;
+pub mod gamepad;
+pub mod helpers;
+pub mod high_scores;
+pub mod layout;
+pub mod menu;
+pub mod race;
+pub mod replay;
+pub mod resources;
+pub mod save;
+pub mod settings;
+pub mod setup;
+pub mod simulation;
+pub mod storage;
+pub mod versus;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, States, Default)]
+pub enum AppState {
+    #[default]
+    StartMenu,
+    InGame,
+    GameOverMenu,
+    Replay,
+    // a run on hold, the simulation doesn't advance until it's back InGame
+    Paused,
+    Versus,
+}
+
+// size, score and looks of a fruit all come from its tier in the FruitCatalog
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fruit {
+    pub tier: usize,
+}
+
+impl Fruit {
+    pub fn merge(&self, catalog: &FruitCatalog) -> Option<Fruit> {
+        if self.tier >= catalog.largest_tier() {
+            return None;
+        }
+        Some(Fruit {
+            tier: self.tier + 1,
+        })
+    }
+}
@@ -1,28 +1,28 @@
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
 
-mod menu;
-use menu::MenuPlugin;
-
-mod setup;
-use setup::SetupPlugin;
-
-mod game;
-use game::GamePlugin;
-
-mod resources;
-use resources::{
-    GameAlreadySetUp, MassSetting, NextGenerator, NoiseSetting, ScoreTracker, SpawnTime,
-};
-
-mod constants;
-use constants::{BG_COLOR, KNOWN_TYPES, SCREEN_HEIGHT, SCREEN_WIDTH};
-
-mod helpers;
+use suika::autoplay::{benchmark, AutoPlayerPlugin, STRATEGIES};
+use suika::catalog::{CatalogPlugin, FruitCatalog};
+use suika::constants::{BG_COLOR, SCREEN_HEIGHT, SCREEN_WIDTH};
+use suika::daily::DailyPlugin;
+use suika::game::GamePlugin;
+use suika::gamepad::GamepadInputPlugin;
+use suika::high_scores::HighScoresPlugin;
+use suika::layout::LayoutPlugin;
+use suika::menu::MenuPlugin;
+use suika::race::{RacePlugin, RaceViewPlugin};
+use suika::replay::ReplayPlugin;
+use suika::resources::GameAlreadySetUp;
+use suika::save::SaveGamePlugin;
+use suika::settings::SettingsPlugin;
+use suika::setup::SetupPlugin;
+use suika::simulation::SimulationPlugin;
+use suika::versus::VersusPlugin;
 
 fn main() {
+    if run_benchmark_from_args() {
+        return;
+    }
     App::new()
-        .add_state::<AppState>()
         .insert_resource(ClearColor(BG_COLOR))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -34,58 +34,60 @@ fn main() {
             }),
             ..default()
         }))
+        // the simulation also brings in the rapier physics plugin
         .add_plugins((
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
-            // RapierDebugRenderPlugin::default(),
+            SimulationPlugin,
+            // bevy_rapier2d::prelude::RapierDebugRenderPlugin::default(),
         ))
-        .init_resource::<SpawnTime>()
-        .init_resource::<NextGenerator>()
         .init_resource::<GameAlreadySetUp>()
-        .init_resource::<ScoreTracker>()
-        .init_resource::<MassSetting>()
-        .init_resource::<NoiseSetting>()
+        .add_plugins(SettingsPlugin)
+        .add_plugins(CatalogPlugin)
+        .add_plugins(LayoutPlugin)
         .add_plugins(SetupPlugin)
         .add_plugins(MenuPlugin)
         .add_plugins(GamePlugin)
+        .add_plugins(GamepadInputPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(HighScoresPlugin)
+        .add_plugins(DailyPlugin)
+        .add_plugins(SaveGamePlugin)
+        .add_plugins(AutoPlayerPlugin)
+        .add_plugins(VersusPlugin)
+        .add_plugins((RacePlugin, RaceViewPlugin))
         .run();
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, States, Default)]
-pub enum AppState {
-    #[default]
-    StartMenu,
-    InGame,
-    GameOverMenu,
-}
-
-#[derive(Component)]
-pub struct Fruit {
-    pub size: f32,
-    pub file_name: String,
-    pub score: u32,
-}
-
-impl Fruit {
-    pub fn merge(&self) -> Option<Fruit> {
-        if self.size == KNOWN_TYPES[10].0 {
-            return None;
-        }
-        let (size, file_name, score) =
-            KNOWN_TYPES[KNOWN_TYPES.iter().position(|s| s.0 == self.size).unwrap() + 1];
-        Some(Fruit {
-            size,
-            file_name: String::from(file_name),
-            score,
-        })
-    }
-}
-
-impl Clone for Fruit {
-    fn clone(&self) -> Fruit {
-        Fruit {
-            size: self.size,
-            file_name: self.file_name.clone(),
-            score: self.score,
+// `suika --benchmark <strategy> [runs]` plays seeded runs headless instead
+fn run_benchmark_from_args() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(i) = args.iter().position(|arg| arg == "--benchmark") else {
+        return false;
+    };
+    let strategy = args.get(i + 1).map(String::as_str).unwrap_or("lookahead");
+    let runs = args
+        .get(i + 2)
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(100);
+    match benchmark(strategy, 0..runs) {
+        Some(report) => {
+            // headless runs always play the built-in roster
+            let highest = report.highest_tier();
+            println!(
+                "{}: {} runs ({} cut off), mean score {:.1}, median score {:.1}, highest tier {} ({})",
+                report.strategy,
+                report.runs.len(),
+                report.truncated_runs(),
+                report.mean_score(),
+                report.median_score(),
+                highest,
+                FruitCatalog::default().fruits[highest].name
+            );
         }
+        None => eprintln!(
+            "unknown strategy {}, expected one of {}",
+            strategy,
+            STRATEGIES.join(", ")
+        ),
     }
+    true
 }
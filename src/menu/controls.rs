@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::constants::{
+    BUTTON_FOCUSED_COLOR, BUTTON_HOVERED_COLOR, BUTTON_PRESSED_COLOR, CONTROL_HEIGHT,
+    CONTROL_WIDTH, NEXT_BG_COLOR, TEXT_COLOR,
+};
+use crate::layout::Layout;
+use crate::resources::{GameAlreadySetUp, MassSetting, NoiseSetting, RecentTouch};
+
+use super::focus::UiFocus;
+use super::noise_toggle::spawn_noise_toggle;
+use super::physics_slider::spawn_slider;
+use super::restart::spawn_restart;
+
+#[derive(Component)]
+pub struct ControlsPanel;
+
+pub fn setup_controls(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_already_set_up: Res<GameAlreadySetUp>,
+    mass_setting: Res<MassSetting>,
+    noise_setting: Res<NoiseSetting>,
+    layout: Res<Layout>,
+) {
+    if game_already_set_up.is_set_up {
+        return;
+    }
+    commands
+        .spawn((
+            ControlsPanel,
+            NodeBundle {
+                style: layout.controls_style(),
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            spawn_restart(builder);
+            spawn_noise_toggle(builder, &noise_setting);
+            spawn_slider(builder, &asset_server, &mass_setting);
+        });
+}
+
+pub fn button_bundle() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            width: Val::Px(CONTROL_WIDTH),
+            height: Val::Px(CONTROL_HEIGHT),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NEXT_BG_COLOR.into(),
+        ..default()
+    }
+}
+
+pub fn label_bundle(label: &str) -> TextBundle {
+    TextBundle::from_section(
+        label,
+        TextStyle {
+            font_size: 25.0,
+            color: TEXT_COLOR,
+            ..default()
+        },
+    )
+}
+
+pub fn update_button_colors(
+    mut controls: Query<(Entity, Ref<Interaction>, &mut BackgroundColor)>,
+    focus: Res<UiFocus>,
+) {
+    for (entity, interaction, mut color) in controls.iter_mut() {
+        if !interaction.is_changed() && !focus.is_changed() {
+            continue;
+        }
+        *color = match *interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None if focus.entity == Some(entity) => BUTTON_FOCUSED_COLOR,
+            Interaction::None => NEXT_BG_COLOR,
+        }
+        .into();
+    }
+}
+
+// a tap followed by the browser's emulated click would press a button twice
+pub fn just_pressed(
+    interaction: &Interaction,
+    touches: &Touches,
+    recent_touch: &RecentTouch,
+) -> bool {
+    *interaction == Interaction::Pressed
+        && (touches.any_just_pressed() || !recent_touch.is_recent())
+}
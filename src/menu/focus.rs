@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::AppState;
+
+// the control enter and the south button press on the menus and the pause screen
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub entity: Option<Entity>,
+    // pressed by a key this frame, let go again at the end of it
+    pressed: Option<Entity>,
+}
+
+// while there is one, focus stays on the controls under it
+#[derive(Component)]
+pub struct FocusRoot;
+
+// in game the same keys aim and drop
+pub fn focus_enabled(state: Res<State<AppState>>) -> bool {
+    matches!(
+        state.get(),
+        AppState::StartMenu | AppState::GameOverMenu | AppState::Paused
+    )
+}
+
+// controls on a hidden page have no size
+fn shown(node: &Node) -> bool {
+    node.size() != Vec2::ZERO
+}
+
+// top to bottom, then left to right
+pub fn move_focus(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    controls: Query<(Entity, &Node, &GlobalTransform), With<Interaction>>,
+    roots: Query<Entity, With<FocusRoot>>,
+    parents: Query<&Parent>,
+    mut focus: ResMut<UiFocus>,
+) {
+    if let Some(entity) = focus.entity {
+        if !controls.get(entity).is_ok_and(|(_, node, _)| shown(node)) {
+            focus.entity = None;
+        }
+    }
+
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let tab = keyboard_input.just_pressed(KeyCode::Tab);
+    let forward = (tab && !shift)
+        || keyboard_input.just_pressed(KeyCode::Down)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadDown]);
+    let back = (tab && shift)
+        || keyboard_input.just_pressed(KeyCode::Up)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadUp]);
+    if forward == back {
+        return;
+    }
+
+    let root = roots.iter().next();
+    let mut order: Vec<(Entity, Vec3)> = controls
+        .iter()
+        .filter(|(_, node, _)| shown(node))
+        .filter(|(entity, ..)| {
+            root.is_none_or(|root| {
+                parents
+                    .iter_ancestors(*entity)
+                    .any(|ancestor| ancestor == root)
+            })
+        })
+        .map(|(entity, _, transform)| (entity, transform.translation()))
+        .collect();
+    if order.is_empty() {
+        return;
+    }
+    order.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)));
+
+    let len = order.len();
+    let current = focus
+        .entity
+        .and_then(|focused| order.iter().position(|(entity, _)| *entity == focused));
+    let index = match (current, forward) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(i), true) => (i + 1) % len,
+        (Some(i), false) => (i + len - 1) % len,
+    };
+    focus.entity = Some(order[index].0);
+}
+
+// after bevy_ui's focus system, so the handlers see it like a click
+pub fn press_focused(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut buttons: Query<&mut Interaction, With<Button>>,
+    mut focus: ResMut<UiFocus>,
+) {
+    let Some(entity) = focus.entity else {
+        return;
+    };
+    if !keyboard_input.just_pressed(KeyCode::Return)
+        && !any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::South])
+    {
+        return;
+    }
+    if let Ok(mut interaction) = buttons.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
+
+// bevy_ui only lets go of a press when the mouse is released
+pub fn release_focused(mut buttons: Query<&mut Interaction>, mut focus: ResMut<UiFocus>) {
+    if focus.pressed.is_none() {
+        return;
+    }
+    if let Some(entity) = focus.pressed.take() {
+        if let Ok(mut interaction) = buttons.get_mut(entity) {
+            *interaction = Interaction::None;
+        }
+    }
+}
@@ -1,56 +1,116 @@
-use bevy::{prelude::*, window::PrimaryWindow};
-use bevy_rapier2d::prelude::RigidBody;
+use bevy::{prelude::*, ui::UiSystem, window::PrimaryWindow};
 
+use crate::catalog::FruitCatalog;
 use crate::constants::{SCREEN_HEIGHT, TEXT_COLOR, TRANSPARENT};
-use crate::resources::{GameAlreadySetUp, ScoreTracker};
-use crate::setup::{MainCamera, Score};
-use crate::{AppState, Fruit};
+use crate::resources::{
+    daily_seed, daily_seed_date, GameAlreadySetUp, GameMode, GameRng, NextSeed, RecentTouch,
+    RunClock, ScoreTracker,
+};
+use crate::setup::MainCamera;
+use crate::simulation::mode_finished;
+use crate::storage;
+use crate::AppState;
 
-use crate::helpers::get_mouse_pos;
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::helpers::{get_mouse_pos, pointer_over_ui};
+
+mod controls;
+pub use controls::{button_bundle, just_pressed, label_bundle, ControlsPanel};
+use controls::{setup_controls, update_button_colors};
+
+mod focus;
+use focus::{focus_enabled, move_focus, press_focused, release_focused};
+pub use focus::{FocusRoot, UiFocus};
 
 mod physics_slider;
-use physics_slider::{handle_slider_change, setup_slider};
+use physics_slider::{handle_slider_change, nudge_focused_slider, sync_slider};
 
 mod noise_toggle;
-use noise_toggle::{handle_noise_toggle, setup_noise_toggle};
+use noise_toggle::handle_noise_toggle;
 
 mod restart;
-use restart::{handle_restart, setup_restart};
+use restart::handle_restart;
+
+mod motion_toggle;
+use motion_toggle::handle_motion_toggle;
+
+mod scoring_toggle;
+use scoring_toggle::handle_scoring_toggle;
+
+mod mode_select;
+use mode_select::{handle_mode_select, spawn_mode_select};
+
+mod pause;
+use pause::{
+    cleanup_pause_menu, handle_pause_menu, pause_on_input, resume_on_input, setup_pause_menu,
+};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_menu)
-            .add_systems(Update, menu_system.run_if(in_state(AppState::StartMenu)))
+        app.add_systems(OnEnter(AppState::StartMenu), setup_menu)
+            .add_systems(
+                Update,
+                (menu_system, seed_input).run_if(in_state(AppState::StartMenu)),
+            )
             .add_systems(OnExit(AppState::StartMenu), cleanup_menu)
             .add_systems(OnEnter(AppState::GameOverMenu), setup_game_over)
             .add_systems(Update, menu_system.run_if(in_state(AppState::GameOverMenu)))
             .add_systems(
-                OnEnter(AppState::InGame),
-                (setup_slider, setup_noise_toggle, setup_restart),
+                Update,
+                handle_mode_select.run_if(
+                    in_state(AppState::StartMenu).or_else(in_state(AppState::GameOverMenu)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::InGame), setup_controls)
+            .init_resource::<UiFocus>()
+            .add_systems(
+                PreUpdate,
+                press_focused.after(UiSystem::Focus).run_if(focus_enabled),
             )
             .add_systems(
                 Update,
-                (handle_slider_change, handle_noise_toggle, handle_restart)
-                    .run_if(in_state(AppState::InGame)),
+                (move_focus, nudge_focused_slider.after(move_focus)).run_if(focus_enabled),
             )
+            .add_systems(Last, release_focused)
+            .add_systems(Update, update_button_colors)
             .add_systems(
-                OnExit(AppState::GameOverMenu),
-                (cleanup_menu, cleanup_fruits),
-            );
+                Update,
+                (
+                    handle_slider_change,
+                    sync_slider.after(handle_slider_change),
+                    handle_noise_toggle,
+                    handle_motion_toggle,
+                    handle_scoring_toggle,
+                    handle_restart,
+                )
+                    .run_if(in_state(AppState::InGame).or_else(in_state(AppState::Paused))),
+            )
+            .add_systems(Update, pause_on_input.run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(AppState::Paused), setup_pause_menu)
+            .add_systems(
+                Update,
+                (resume_on_input, handle_pause_menu).run_if(in_state(AppState::Paused)),
+            )
+            .add_systems(OnExit(AppState::Paused), cleanup_pause_menu)
+            .add_systems(OnExit(AppState::GameOverMenu), cleanup_menu);
     }
 }
 
 #[derive(Component)]
 pub struct MenuItem;
 
-fn setup_menu(mut commands: Commands) {
+#[derive(Component)]
+pub struct SeedText;
+
+fn setup_menu(mut commands: Commands, game_mode: Res<GameMode>) {
+    spawn_mode_select(&mut commands, &game_mode);
     commands.spawn((
         MenuItem,
         Text2dBundle {
             text: Text::from_section(
-                "click anywhere to begin",
+                "click anywhere or press start to begin",
                 TextStyle {
                     font_size: 30.0,
                     color: TEXT_COLOR,
@@ -61,6 +121,86 @@ fn setup_menu(mut commands: Commands) {
             ..default()
         },
     ));
+    commands.spawn((
+        MenuItem,
+        SeedText,
+        Text2dBundle {
+            text: Text::from_section(
+                seed_text_value(None, *game_mode),
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, -50.0, 0.0),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        MenuItem,
+        Text2dBundle {
+            text: Text::from_section(
+                "press V for a two-player versus match",
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, 85.0, 0.0),
+            ..default()
+        },
+    ));
+}
+
+fn seed_text_value(seed: Option<u64>, game_mode: GameMode) -> String {
+    match (seed, game_mode) {
+        (_, GameMode::Daily) => format!(
+            "seed: daily challenge for {}",
+            daily_seed_date(daily_seed(storage::now_unix_secs()))
+        ),
+        (Some(seed), _) => format!("seed: {}", seed),
+        (None, _) => String::from("seed: random (type a number to set one)"),
+    }
+}
+
+// the daily challenge always uses the day's seed
+fn seed_input(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    game_mode: Res<GameMode>,
+    mut next_seed: ResMut<NextSeed>,
+    mut seed_text: Query<&mut Text, With<SeedText>>,
+) {
+    if *game_mode == GameMode::Daily {
+        received_characters.clear();
+        if next_seed.seed.is_some() {
+            next_seed.seed = None;
+        }
+    }
+    for received in received_characters.iter() {
+        if let Some(digit) = received.char.to_digit(10) {
+            let seed = next_seed.seed.unwrap_or(0);
+            next_seed.seed = seed
+                .checked_mul(10)
+                .and_then(|s| s.checked_add(digit as u64));
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        next_seed.seed = next_seed
+            .seed
+            .map(|seed| seed / 10)
+            .filter(|seed| *seed != 0);
+    }
+
+    if next_seed.is_changed() || game_mode.is_changed() {
+        if let Ok(mut text) = seed_text.get_single_mut() {
+            text.sections[0].value = seed_text_value(next_seed.seed, *game_mode);
+        }
+    }
 }
 
 fn cleanup_menu(mut commands: Commands, menu_items: Query<Entity, With<MenuItem>>) {
@@ -72,9 +212,14 @@ fn cleanup_menu(mut commands: Commands, menu_items: Query<Entity, With<MenuItem>
 fn setup_game_over(
     mut commands: Commands,
     mut game_already_set_up: ResMut<GameAlreadySetUp>,
-    mut fruits: Query<&mut RigidBody, With<Fruit>>,
+    game_rng: Res<GameRng>,
+    game_mode: Res<GameMode>,
+    score_tracker: Res<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    catalog: Res<FruitCatalog>,
 ) {
     game_already_set_up.is_set_up = true;
+    spawn_mode_select(&mut commands, &game_mode);
     commands
         .spawn((
             MenuItem,
@@ -91,7 +236,7 @@ fn setup_game_over(
         .with_children(|builder| {
             builder.spawn((Text2dBundle {
                 text: Text::from_section(
-                    "click anywhere to restart",
+                    "click anywhere or press start to restart",
                     TextStyle {
                         font_size: 30.0,
                         color: TEXT_COLOR,
@@ -120,7 +265,7 @@ fn setup_game_over(
         .with_children(|builder| {
             builder.spawn((Text2dBundle {
                 text: Text::from_section(
-                    "GAME OVER",
+                    finish_title(&score_tracker, &run_clock, &catalog),
                     TextStyle {
                         font_size: 50.0,
                         color: TEXT_COLOR,
@@ -133,36 +278,78 @@ fn setup_game_over(
             },));
         });
 
-    for mut fruit in fruits.iter_mut() {
-        *fruit = RigidBody::Fixed;
-    }
+    commands
+        .spawn((
+            MenuItem,
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(50.0, 50.0)),
+                    color: TRANSPARENT,
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0 - 250.0, 0.0),
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn((Text2dBundle {
+                text: Text::from_section(
+                    format!("seed: {}", game_rng.seed),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(Vec3::Z),
+                ..default()
+            },));
+        });
 }
 
-fn cleanup_fruits(
-    mut commands: Commands,
-    mut score_tracker: ResMut<ScoreTracker>,
-    fruits: Query<Entity, With<Fruit>>,
-    mut score_query: Query<&mut Text, With<Score>>,
-) {
-    score_tracker.reset();
-    let mut score = score_query.single_mut();
-    score.sections[0].value = score_tracker.score.to_string();
-    for fruit in fruits.iter() {
-        commands.entity(fruit).despawn_recursive();
+// runs that ended by reaching their mode's goal say so instead
+fn finish_title(
+    score_tracker: &ScoreTracker,
+    run_clock: &RunClock,
+    catalog: &FruitCatalog,
+) -> &'static str {
+    if !mode_finished(score_tracker, run_clock, catalog) {
+        return "GAME OVER";
+    }
+    match score_tracker.mode {
+        GameMode::TimeAttack => "TIME UP",
+        GameMode::Target => "TARGET REACHED",
+        GameMode::Classic | GameMode::Zen | GameMode::Daily => "GAME OVER",
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn menu_system(
     mut next_state: ResMut<NextState<AppState>>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     mouse_button_input: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    ui_interactions: Query<&Interaction, With<Node>>,
+    focus: Res<UiFocus>,
 ) {
-    let mouse_pos = get_mouse_pos(q_windows, camera_q);
+    let mouse_pos = get_mouse_pos(&q_windows, &camera_q);
 
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        if let Some(_world_position) = mouse_pos {
-            next_state.set(AppState::InGame);
-        }
+    // presses on the mode button only change the mode
+    let clicked = ((!recent_touch.is_recent()
+        && mouse_button_input.just_pressed(MouseButton::Left)
+        && mouse_pos.is_some())
+        || touches.any_just_pressed())
+        && !pointer_over_ui(&ui_interactions);
+    // south presses the focused control instead when there is one
+    let pressed = any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::Start])
+        || (focus.entity.is_none()
+            && any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::South]));
+    if clicked || pressed {
+        next_state.set(AppState::InGame);
     }
 }
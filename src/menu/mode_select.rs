@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::constants::CONTROLS_RIGHT;
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::layout::Layout;
+use crate::resources::{GameMode, RecentTouch};
+
+use super::controls::{button_bundle, just_pressed, label_bundle};
+use super::MenuItem;
+
+#[derive(Component)]
+pub struct ModeButton;
+
+#[derive(Component)]
+pub struct ModeText;
+
+// the mode it shows is the one the next run uses
+pub fn spawn_mode_select(commands: &mut Commands, game_mode: &GameMode) {
+    let mut button = button_bundle();
+    button.style.position_type = PositionType::Absolute;
+    button.style.top = Val::Px(Layout::menu_control_top(0));
+    button.style.right = Val::Px(CONTROLS_RIGHT);
+    commands
+        .spawn((MenuItem, ModeButton, button))
+        .with_children(|builder| {
+            builder.spawn((ModeText, label_bundle(&mode_label(*game_mode))));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_mode_select(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ModeButton>)>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut game_mode: ResMut<GameMode>,
+    mut mode_text: Query<&mut Text, With<ModeText>>,
+) {
+    let mut steps = 0;
+    for interaction in buttons.iter() {
+        if just_pressed(interaction, &touches, &recent_touch) {
+            steps += 1;
+        }
+    }
+    if keyboard_input.any_just_pressed([KeyCode::M, KeyCode::Right])
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadRight])
+    {
+        steps += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Left)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadLeft])
+    {
+        steps -= 1;
+    }
+    if steps == 0 {
+        return;
+    }
+
+    *game_mode = game_mode.cycle(steps);
+    for mut text in mode_text.iter_mut() {
+        text.sections[0].value = mode_label(*game_mode);
+    }
+}
+
+fn mode_label(game_mode: GameMode) -> String {
+    format!("mode: {}", game_mode.name())
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::resources::{RecentTouch, ReducedMotion};
+
+use super::controls::{button_bundle, just_pressed, label_bundle};
+
+#[derive(Component)]
+pub struct MotionToggleButton;
+
+#[derive(Component)]
+pub struct MotionText;
+
+pub fn spawn_motion_toggle(builder: &mut ChildBuilder, reduced_motion: &ReducedMotion) {
+    builder
+        .spawn((MotionToggleButton, button_bundle()))
+        .with_children(|builder| {
+            builder.spawn((MotionText, label_bundle(motion_label(reduced_motion.is_on))));
+        });
+}
+
+pub fn handle_motion_toggle(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<MotionToggleButton>)>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    mut motion_text: Query<&mut Text, With<MotionText>>,
+    mut reduced_motion: ResMut<ReducedMotion>,
+) {
+    for interaction in buttons.iter() {
+        if !just_pressed(interaction, &touches, &recent_touch) {
+            continue;
+        }
+        reduced_motion.toggle();
+        for mut text in motion_text.iter_mut() {
+            text.sections[0].value = String::from(motion_label(reduced_motion.is_on));
+        }
+    }
+}
+
+fn motion_label(is_on: bool) -> &'static str {
+    if is_on {
+        "effects: reduced"
+    } else {
+        "effects: full"
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::{prelude::*, ui::FocusPolicy, window::WindowFocused};
+
+use crate::constants::{PAUSE_OVERLAY_COLOR, TEXT_COLOR};
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::resources::{
+    MassSetting, NoiseSetting, RecentTouch, ReducedMotion, ScoreTracker, ScoringRules,
+};
+use crate::simulation::RestartGame;
+use crate::AppState;
+
+use super::controls::{button_bundle, just_pressed, label_bundle};
+use super::focus::FocusRoot;
+use super::motion_toggle::spawn_motion_toggle;
+use super::noise_toggle::spawn_noise_toggle;
+use super::physics_slider::spawn_slider;
+use super::scoring_toggle::spawn_scoring_toggle;
+
+#[derive(Component)]
+pub struct PauseOverlay;
+
+#[derive(Component)]
+pub enum PausePage {
+    Actions,
+    Settings,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    // there is nothing to quit to in the browser
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+fn toggle_pressed(
+    keyboard_input: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_input: &Input<GamepadButton>,
+) -> bool {
+    keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::P])
+        || any_gamepad_just_pressed(gamepads, gamepad_input, [GamepadButtonType::Start])
+}
+
+pub fn pause_on_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut window_focused: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // switching tabs or apps shouldn't cost the run
+    let focus_lost = window_focused.iter().any(|focus| !focus.focused);
+    if focus_lost || toggle_pressed(&keyboard_input, &gamepads, &gamepad_input) {
+        next_state.set(AppState::Paused);
+    }
+}
+
+pub fn resume_on_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if toggle_pressed(&keyboard_input, &gamepads, &gamepad_input) {
+        next_state.set(AppState::InGame);
+    }
+}
+
+pub fn setup_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mass_setting: Res<MassSetting>,
+    noise_setting: Res<NoiseSetting>,
+    reduced_motion: Res<ReducedMotion>,
+    scoring_rules: Res<ScoringRules>,
+    score_tracker: Res<ScoreTracker>,
+) {
+    commands
+        .spawn((
+            PauseOverlay,
+            // not the in-game controls underneath
+            FocusRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: PAUSE_OVERLAY_COLOR.into(),
+                // keeps the controls underneath from being clicked through the overlay
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section(
+                "PAUSED",
+                TextStyle {
+                    font_size: 50.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
+
+            builder
+                .spawn((PausePage::Actions, page_bundle(Display::Flex)))
+                .with_children(|builder| {
+                    let actions = [
+                        (PauseAction::Resume, "resume"),
+                        (PauseAction::Restart, "restart"),
+                        (PauseAction::Settings, "settings"),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        (PauseAction::Quit, "quit"),
+                    ];
+                    for (action, label) in actions {
+                        spawn_action(builder, action, label);
+                    }
+                });
+
+            builder
+                .spawn((PausePage::Settings, page_bundle(Display::None)))
+                .with_children(|builder| {
+                    spawn_noise_toggle(builder, &noise_setting);
+                    spawn_motion_toggle(builder, &reduced_motion);
+                    spawn_scoring_toggle(builder, &scoring_rules, &score_tracker);
+                    spawn_slider(builder, &asset_server, &mass_setting);
+                    spawn_action(builder, PauseAction::Settings, "back");
+                });
+        });
+}
+
+fn page_bundle(display: Display) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            display,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(20.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn spawn_action(builder: &mut ChildBuilder, action: PauseAction, label: &str) {
+    builder
+        .spawn((action, button_bundle()))
+        .with_children(|builder| {
+            builder.spawn(label_bundle(label));
+        });
+}
+
+pub fn handle_pause_menu(
+    buttons: Query<(&Interaction, &PauseAction), Changed<Interaction>>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    mut pages: Query<&mut Style, With<PausePage>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart_requests: EventWriter<RestartGame>,
+    #[cfg(not(target_arch = "wasm32"))] mut app_exit: EventWriter<bevy::app::AppExit>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if !just_pressed(interaction, &touches, &recent_touch) {
+            continue;
+        }
+        match action {
+            PauseAction::Resume => next_state.set(AppState::InGame),
+            PauseAction::Restart => {
+                restart_requests.send(RestartGame);
+                next_state.set(AppState::InGame);
+            }
+            // switches between the actions and the settings page
+            PauseAction::Settings => {
+                for mut style in pages.iter_mut() {
+                    style.display = match style.display {
+                        Display::None => Display::Flex,
+                        _ => Display::None,
+                    };
+                }
+            }
+            // the run is saved on the way out
+            #[cfg(not(target_arch = "wasm32"))]
+            PauseAction::Quit => app_exit.send(bevy::app::AppExit),
+        }
+    }
+}
+
+pub fn cleanup_pause_menu(mut commands: Commands, overlay: Query<Entity, With<PauseOverlay>>) {
+    for overlay in overlay.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
@@ -1,172 +1,163 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
 
-use crate::helpers::{get_mouse_pos, mouse_pos_in_slider};
-use crate::resources::{GameAlreadySetUp, MassSetting};
-use crate::setup::MainCamera;
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::resources::MassSetting;
 
 use crate::constants::{
-    NEXT_BG_COLOR, PREVIEW_HINT_COLOR, SLIDER_CONTAINER_HEIGHT, SLIDER_CONTAINER_SIDES,
-    SLIDER_CONTAINER_WIDTH, SLIDER_POS_X, SLIDER_POS_Y, SLIDER_WIDTH, TEXT_COLOR, TRANSPARENT,
-    YAGOO_SIZE,
+    CONTROL_HEIGHT, CONTROL_WIDTH, NEXT_BG_COLOR, PREVIEW_HINT_COLOR, SLIDER_WIDTH, YAGOO_SIZE,
 };
 
+use super::controls::label_bundle;
+use super::focus::UiFocus;
+
+const FOCUSED_SLIDER_STEP: f32 = 0.1;
+
+#[derive(Component)]
+pub struct PhysicsSlider;
+
 #[derive(Component)]
 pub struct Yagoo;
 
-pub fn setup_slider(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    game_already_set_up: Res<GameAlreadySetUp>,
+pub fn spawn_slider(
+    builder: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    mass_setting: &MassSetting,
 ) {
-    if game_already_set_up.is_set_up {
-        return;
-    }
-    commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(SLIDER_CONTAINER_WIDTH, SLIDER_CONTAINER_HEIGHT)),
-                color: NEXT_BG_COLOR,
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
                 ..default()
             },
-            transform: Transform::from_xyz(SLIDER_POS_X, SLIDER_POS_Y, 0.0),
             ..default()
         })
         .with_children(|builder| {
-            builder.spawn(SpriteBundle {
-                sprite: Sprite {
-                    custom_size: Some(Vec2::new(SLIDER_WIDTH, 5.0)),
-                    color: PREVIEW_HINT_COLOR,
-                    ..default()
-                },
-                transform: Transform::from_xyz(0.0, 0.0, 1.0),
-                ..default()
-            });
-
-            builder.spawn((
-                Yagoo,
-                SpriteBundle {
-                    sprite: Sprite {
-                        custom_size: Some(Vec2::new(1.0, 1.0) * 50.0),
+            builder.spawn(label_bundle("physics"));
+            builder
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(CONTROL_WIDTH),
+                        justify_content: JustifyContent::SpaceAround,
                         ..default()
                     },
-                    texture: asset_server.load("yagoo.png"),
-                    transform: Transform::from_xyz(0.0, 0.0, 2.0),
                     ..default()
-                },
-            ));
-        });
-
-    commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(SLIDER_CONTAINER_WIDTH, SLIDER_CONTAINER_HEIGHT)),
-                color: TRANSPARENT,
-                ..default()
-            },
-            transform: Transform::from_xyz(
-                SLIDER_POS_X,
-                SLIDER_POS_Y + SLIDER_CONTAINER_HEIGHT,
-                0.0,
-            ),
-            ..default()
-        })
-        .with_children(|builder| {
-            builder.spawn(Text2dBundle {
-                text: Text::from_section(
-                    "physics",
-                    TextStyle {
-                        font_size: 25.0,
-                        color: TEXT_COLOR,
-                        ..default()
-                    },
-                )
-                .with_alignment(TextAlignment::Center),
-                transform: Transform::from_translation(Vec3::Z),
-                ..default()
-            });
+                })
+                .with_children(|builder| {
+                    builder.spawn(label_bundle("stable"));
+                    builder.spawn(label_bundle("crazy"));
+                });
+            spawn_track(builder, asset_server, mass_setting);
         });
+}
 
-    commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(SLIDER_CONTAINER_WIDTH, SLIDER_CONTAINER_HEIGHT)),
-                color: TRANSPARENT,
+fn spawn_track(builder: &mut ChildBuilder, asset_server: &AssetServer, mass_setting: &MassSetting) {
+    builder
+        .spawn((
+            PhysicsSlider,
+            // an Interaction makes the slider capture the pointer like a button would
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(CONTROL_WIDTH),
+                    height: Val::Px(CONTROL_HEIGHT),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NEXT_BG_COLOR.into(),
                 ..default()
             },
-            transform: Transform::from_xyz(SLIDER_POS_X - 75.0, SLIDER_POS_Y + 45.0, 0.0),
-            ..default()
-        })
+        ))
         .with_children(|builder| {
-            builder.spawn(Text2dBundle {
-                text: Text::from_section(
-                    "stable",
-                    TextStyle {
-                        font_size: 25.0,
-                        color: TEXT_COLOR,
-                        ..default()
-                    },
-                )
-                .with_alignment(TextAlignment::Center),
-                transform: Transform::from_translation(Vec3::Z),
+            builder.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(SLIDER_WIDTH),
+                    height: Val::Px(5.0),
+                    ..default()
+                },
+                background_color: PREVIEW_HINT_COLOR.into(),
                 ..default()
             });
-        });
 
-    commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(SLIDER_CONTAINER_WIDTH, SLIDER_CONTAINER_HEIGHT)),
-                color: TRANSPARENT,
-                ..default()
-            },
-            transform: Transform::from_xyz(SLIDER_POS_X + 75.0, SLIDER_POS_Y + 45.0, 0.0),
-            ..default()
-        })
-        .with_children(|builder| {
-            builder.spawn(Text2dBundle {
-                text: Text::from_section(
-                    "crazy",
-                    TextStyle {
-                        font_size: 25.0,
-                        color: TEXT_COLOR,
+            builder.spawn((
+                Yagoo,
+                ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(yagoo_left(1.0 - mass_setting.percentage)),
+                        width: Val::Px(YAGOO_SIZE),
+                        height: Val::Px(YAGOO_SIZE),
                         ..default()
                     },
-                )
-                .with_alignment(TextAlignment::Center),
-                transform: Transform::from_translation(Vec3::Z),
-                ..default()
-            });
+                    image: UiImage::new(asset_server.load("yagoo.png")),
+                    ..default()
+                },
+            ));
         });
 }
 
+// bevy_ui keeps the slider pressed while the pointer is dragged off it
 pub fn handle_slider_change(
-    mouse_button_input: Res<Input<MouseButton>>,
-    q_windows: Query<&Window, With<PrimaryWindow>>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut yagoo: Query<&mut Transform, With<Yagoo>>,
+    slider: Query<(&Interaction, &RelativeCursorPosition), With<PhysicsSlider>>,
     mut mass_setting: ResMut<MassSetting>,
 ) {
-    let yagoo_max: f32 = SLIDER_CONTAINER_WIDTH / 2.0 - YAGOO_SIZE / 2.0;
-    let yagoo_min: f32 = -SLIDER_CONTAINER_WIDTH / 2.0 + YAGOO_SIZE / 2.0;
-    let mouse_pos = get_mouse_pos(q_windows, camera_q);
-
-    if mouse_button_input.pressed(MouseButton::Left) {
-        if let Some(world_position) = mouse_pos {
-            if mouse_pos_in_slider(world_position) {
-                let offset = (SLIDER_CONTAINER_WIDTH - SLIDER_WIDTH) / 2.0;
-                let max_x = SLIDER_CONTAINER_SIDES.1 - offset;
-                let min_x = SLIDER_CONTAINER_SIDES.3 + offset;
-                let slider_center = (SLIDER_CONTAINER_SIDES.1 - SLIDER_CONTAINER_SIDES.3) / 2.0;
-                let mouse_x = match world_position.x {
-                    x if x < slider_center => x.max(min_x),
-                    x if x > slider_center => x.min(max_x),
-                    _ => world_position.x,
-                };
-                let percentage = (mouse_x - min_x) / (max_x - min_x);
-                let mut yagoo_transform = yagoo.single_mut();
-                yagoo_transform.translation.x = (yagoo_max - yagoo_min) * percentage + yagoo_min;
-                mass_setting.percentage = 1.0 - percentage;
-            }
+    for (interaction, cursor) in slider.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
         }
+        if let Some(normalized) = cursor.normalized {
+            mass_setting.percentage = 1.0 - slider_percentage(normalized.x * CONTROL_WIDTH);
+        }
+    }
+}
+
+// the setting also changes without dragging him
+pub fn sync_slider(mass_setting: Res<MassSetting>, mut yagoo: Query<&mut Style, With<Yagoo>>) {
+    if !mass_setting.is_changed() {
+        return;
     }
+    for mut yagoo_style in yagoo.iter_mut() {
+        yagoo_style.left = Val::Px(yagoo_left(1.0 - mass_setting.percentage));
+    }
+}
+
+// yagoo is further right the lower the percentage
+pub fn nudge_focused_slider(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    focus: Res<UiFocus>,
+    slider: Query<(), With<PhysicsSlider>>,
+    mut mass_setting: ResMut<MassSetting>,
+) {
+    if !focus.entity.is_some_and(|entity| slider.contains(entity)) {
+        return;
+    }
+    let mut step = 0.0;
+    if keyboard_input.just_pressed(KeyCode::Left)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadLeft])
+    {
+        step += FOCUSED_SLIDER_STEP;
+    }
+    if keyboard_input.just_pressed(KeyCode::Right)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::DPadRight])
+    {
+        step -= FOCUSED_SLIDER_STEP;
+    }
+    if step != 0.0 {
+        mass_setting.percentage = (mass_setting.percentage + step).clamp(0.0, 1.0);
+    }
+}
+
+// x from the slider's left edge
+fn slider_percentage(x: f32) -> f32 {
+    let offset = (CONTROL_WIDTH - SLIDER_WIDTH) / 2.0;
+    (x.clamp(offset, CONTROL_WIDTH - offset) - offset) / SLIDER_WIDTH
+}
+
+fn yagoo_left(percentage: f32) -> f32 {
+    (CONTROL_WIDTH - YAGOO_SIZE) * percentage
 }
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::resources::{RecentTouch, ScoreTracker, ScoringRules};
+
+use super::controls::{button_bundle, just_pressed, label_bundle};
+
+#[derive(Component)]
+pub struct ScoringToggleButton;
+
+#[derive(Component)]
+pub struct ScoringText;
+
+pub fn spawn_scoring_toggle(
+    builder: &mut ChildBuilder,
+    scoring_rules: &ScoringRules,
+    score_tracker: &ScoreTracker,
+) {
+    builder
+        .spawn((ScoringToggleButton, button_bundle()))
+        .with_children(|builder| {
+            builder.spawn((
+                ScoringText,
+                label_bundle(&scoring_label(*scoring_rules, score_tracker.rules)),
+            ));
+        });
+}
+
+pub fn handle_scoring_toggle(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ScoringToggleButton>)>,
+    touches: Res<Touches>,
+    recent_touch: Res<RecentTouch>,
+    mut scoring_text: Query<&mut Text, With<ScoringText>>,
+    mut scoring_rules: ResMut<ScoringRules>,
+    score_tracker: Res<ScoreTracker>,
+) {
+    for interaction in buttons.iter() {
+        if !just_pressed(interaction, &touches, &recent_touch) {
+            continue;
+        }
+        scoring_rules.toggle();
+        for mut text in scoring_text.iter_mut() {
+            text.sections[0].value = scoring_label(*scoring_rules, score_tracker.rules);
+        }
+    }
+}
+
+// the current run keeps its rules, so say when the choice only applies later
+fn scoring_label(chosen: ScoringRules, current: ScoringRules) -> String {
+    let name = match chosen {
+        ScoringRules::Classic => "classic",
+        ScoringRules::Combo => "combo",
+    };
+    if chosen == current {
+        format!("scoring: {}", name)
+    } else {
+        format!("scoring: {} (next run)", name)
+    }
+}
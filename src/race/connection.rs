@@ -0,0 +1,222 @@
+// a background thread natively, the browser's WebSocket in wasm
+
+use super::protocol::{decode, encode, ClientMessage, ServerMessage};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::RaceConnection;
+#[cfg(target_arch = "wasm32")]
+pub use web::RaceConnection;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::net::TcpStream;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use bevy::log::warn;
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::{Message, WebSocket};
+
+    use super::*;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    pub struct RaceConnection {
+        outgoing: Sender<ClientMessage>,
+        incoming: Receiver<ServerMessage>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl RaceConnection {
+        pub fn open(url: &str) -> Self {
+            let (outgoing, to_send) = mpsc::channel();
+            let (received, incoming) = mpsc::channel();
+            let closed = Arc::new(AtomicBool::new(false));
+            let url = url.to_string();
+            let thread_closed = closed.clone();
+            thread::spawn(move || {
+                match tungstenite::connect(url.as_str()) {
+                    Ok((socket, _)) => run_socket(socket, to_send, received),
+                    Err(err) => warn!("could not connect to the race server at {}: {}", url, err),
+                }
+                thread_closed.store(true, Ordering::Relaxed);
+            });
+            Self {
+                outgoing,
+                incoming,
+                closed,
+            }
+        }
+
+        pub fn send(&mut self, message: &ClientMessage) {
+            // a closed connection has nobody to send to
+            let _ = self.outgoing.send(message.clone());
+        }
+
+        pub fn receive(&mut self) -> Vec<ServerMessage> {
+            self.incoming.try_iter().collect()
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.closed.load(Ordering::Relaxed)
+        }
+    }
+
+    fn run_socket(
+        mut socket: WebSocket<MaybeTlsStream<TcpStream>>,
+        to_send: Receiver<ClientMessage>,
+        received: Sender<ServerMessage>,
+    ) {
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            if let Err(err) = stream.set_read_timeout(Some(POLL_INTERVAL)) {
+                warn!("could not set up the race connection: {}", err);
+                return;
+            }
+        }
+        loop {
+            loop {
+                match to_send.try_recv() {
+                    Ok(message) => {
+                        if socket.send(Message::Text(encode(&message))).is_err() {
+                            return;
+                        }
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        let _ = socket.close(None);
+                        return;
+                    }
+                }
+            }
+            match socket.read() {
+                Ok(Message::Text(text)) => match decode(&text) {
+                    Some(message) => {
+                        if received.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    None => warn!("unexpected message from the race server: {}", text),
+                },
+                Ok(Message::Close(_)) => return,
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err)) if is_timeout(&err) => {}
+                Err(_) => return,
+            }
+        }
+    }
+
+    // a read timing out comes back as WouldBlock on unix and TimedOut on windows
+    pub(crate) fn is_timeout(err: &std::io::Error) -> bool {
+        matches!(
+            err.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use native::is_timeout;
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use bevy::log::warn;
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{Event, MessageEvent, WebSocket};
+
+    use super::*;
+
+    pub struct RaceConnection {
+        socket: Option<WebSocket>,
+        incoming: Rc<RefCell<VecDeque<String>>>,
+        closed: Rc<Cell<bool>>,
+        // sends made while the socket was still connecting
+        queued: Vec<String>,
+        // the callbacks have to live as long as the socket
+        _on_message: Option<Closure<dyn FnMut(MessageEvent)>>,
+        _on_close: Option<Closure<dyn FnMut(Event)>>,
+    }
+
+    impl RaceConnection {
+        pub fn open(url: &str) -> Self {
+            let mut connection = Self {
+                socket: None,
+                incoming: Rc::default(),
+                closed: Rc::new(Cell::new(false)),
+                queued: Vec::new(),
+                _on_message: None,
+                _on_close: None,
+            };
+            let socket = match WebSocket::new(url) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    warn!("could not connect to the race server at {}: {:?}", url, err);
+                    connection.closed.set(true);
+                    return connection;
+                }
+            };
+
+            let incoming = connection.incoming.clone();
+            let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                if let Some(text) = event.data().as_string() {
+                    incoming.borrow_mut().push_back(text);
+                }
+            });
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            let closed = connection.closed.clone();
+            let on_close = Closure::<dyn FnMut(Event)>::new(move |_: Event| closed.set(true));
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            connection.socket = Some(socket);
+            connection._on_message = Some(on_message);
+            connection._on_close = Some(on_close);
+            connection
+        }
+
+        pub fn send(&mut self, message: &ClientMessage) {
+            self.queued.push(encode(message));
+            self.flush();
+        }
+
+        pub fn receive(&mut self) -> Vec<ServerMessage> {
+            self.flush();
+            self.incoming
+                .borrow_mut()
+                .drain(..)
+                .filter_map(|text| {
+                    let message = decode(&text);
+                    if message.is_none() {
+                        warn!("unexpected message from the race server: {}", text);
+                    }
+                    message
+                })
+                .collect()
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.closed.get()
+        }
+
+        fn flush(&mut self) {
+            let Some(socket) = &self.socket else {
+                return;
+            };
+            if socket.ready_state() != WebSocket::OPEN {
+                return;
+            }
+            for text in self.queued.drain(..) {
+                if socket.send_with_str(&text).is_err() {
+                    self.closed.set(true);
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use crate::resources::{GameMode, GameRng, NextSeed, ScoreTracker};
+use crate::simulation::{GameOver, MarkForDelete, RestartGame};
+use crate::{AppState, Fruit};
+
+pub mod protocol;
+use protocol::{ClientMessage, MiniFruit, Progress, ServerMessage};
+
+mod connection;
+pub use connection::RaceConnection;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod relay;
+#[cfg(not(target_arch = "wasm32"))]
+pub use relay::run_relay;
+
+mod view;
+pub use view::RaceViewPlugin;
+
+// seconds between progress updates while racing
+const PROGRESS_INTERVAL: f32 = 0.2;
+const DEFAULT_ROOM: &str = "lobby";
+
+// everyone in a room plays a classic run on the same seed, connects if there are RaceSettings
+pub struct RacePlugin;
+
+impl Plugin for RacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Race>()
+            .add_event::<StartRace>()
+            .add_systems(
+                Startup,
+                (race_from_args, apply_deferred, connect_to_race).chain(),
+            )
+            .add_systems(
+                Update,
+                (receive_race_messages, send_race_start, send_race_progress).run_if(race_connected),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct RaceSettings {
+    // e.g. ws://localhost:9001
+    pub url: String,
+    pub room: String,
+    pub name: String,
+}
+
+#[derive(Resource, Default)]
+pub struct Race {
+    pub room: Option<String>,
+    // our id in the room, once the server has welcomed us
+    pub id: Option<u32>,
+    pub opponents: Vec<Opponent>,
+    // seed of the race this player is running, None between races
+    pub seed: Option<u64>,
+    pub disconnected: bool,
+}
+
+pub struct Opponent {
+    pub id: u32,
+    pub name: String,
+    pub progress: Progress,
+}
+
+// it's on once Race::seed is set
+#[derive(Event)]
+pub struct StartRace {
+    pub seed: Option<u64>,
+}
+
+// the room is set along with the connection, run conditions can't take non-send resources
+pub fn race_connected(race: Res<Race>) -> bool {
+    race.room.is_some()
+}
+
+fn race_from_args(mut commands: Commands) {
+    if let Some(settings) = race_settings_from_environment() {
+        commands.insert_resource(settings);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn race_settings_from_environment() -> Option<RaceSettings> {
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|arg| arg == "--race")?;
+    let arg = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    Some(RaceSettings {
+        url: args.get(i + 1)?.clone(),
+        room: args
+            .get(i + 2)
+            .filter(|room| !room.starts_with("--"))
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_ROOM.to_string()),
+        name: arg("--name").unwrap_or_else(default_name),
+    })
+}
+
+// the page's query string, e.g. ?race=ws%3A%2F%2Flocalhost%3A9001&room=friends&name=pekora
+#[cfg(target_arch = "wasm32")]
+fn race_settings_from_environment() -> Option<RaceSettings> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let param = |name: &str| {
+        search
+            .trim_start_matches('?')
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| js_sys::decode_uri_component(value).ok())
+            .map(String::from)
+    };
+    Some(RaceSettings {
+        url: param("race")?,
+        room: param("room").unwrap_or_else(|| DEFAULT_ROOM.to_string()),
+        name: param("name").unwrap_or_else(default_name),
+    })
+}
+
+fn default_name() -> String {
+    format!("player {}", rand::random::<u16>() % 1000)
+}
+
+fn connect_to_race(world: &mut World) {
+    let Some(settings) = world.get_resource::<RaceSettings>().cloned() else {
+        return;
+    };
+    let mut connection = RaceConnection::open(&settings.url);
+    connection.send(&ClientMessage::Join {
+        room: settings.room.clone(),
+        name: settings.name,
+    });
+    world.insert_non_send_resource(connection);
+    world.resource_mut::<Race>().room = Some(settings.room);
+}
+
+// a race starts a classic run on its seed right away, even over the current one
+fn receive_race_messages(
+    mut connection: NonSendMut<RaceConnection>,
+    mut race: ResMut<Race>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_seed: ResMut<NextSeed>,
+    mut game_mode: ResMut<GameMode>,
+    mut restart: EventWriter<RestartGame>,
+) {
+    if connection.is_closed() && !race.disconnected {
+        warn!("lost the connection to the race server");
+        race.disconnected = true;
+    }
+    for message in connection.receive() {
+        match message {
+            ServerMessage::Welcome { id, players } => {
+                race.id = Some(id);
+                race.opponents = players
+                    .into_iter()
+                    .map(|player| Opponent {
+                        id: player.id,
+                        name: player.name,
+                        progress: Progress::default(),
+                    })
+                    .collect();
+            }
+            ServerMessage::Joined(player) => race.opponents.push(Opponent {
+                id: player.id,
+                name: player.name,
+                progress: Progress::default(),
+            }),
+            ServerMessage::Left { id } => race.opponents.retain(|opponent| opponent.id != id),
+            ServerMessage::Progress { id, progress } => {
+                if let Some(opponent) = race.opponents.iter_mut().find(|opponent| opponent.id == id)
+                {
+                    opponent.progress = progress;
+                }
+            }
+            ServerMessage::Started { seed } => {
+                match state.get() {
+                    AppState::StartMenu | AppState::GameOverMenu => {
+                        next_state.set(AppState::InGame)
+                    }
+                    AppState::InGame => restart.send(RestartGame),
+                    AppState::Paused => {
+                        restart.send(RestartGame);
+                        next_state.set(AppState::InGame);
+                    }
+                    AppState::Replay | AppState::Versus => {
+                        info!("a race started, only the menus or a run can join it");
+                        continue;
+                    }
+                }
+                race.seed = Some(seed);
+                for opponent in race.opponents.iter_mut() {
+                    opponent.progress = Progress::default();
+                }
+                next_seed.seed = Some(seed);
+                if *game_mode != GameMode::Classic {
+                    *game_mode = GameMode::Classic;
+                }
+            }
+        }
+    }
+}
+
+fn send_race_start(
+    mut connection: NonSendMut<RaceConnection>,
+    mut requests: EventReader<StartRace>,
+) {
+    for request in requests.iter() {
+        connection.send(&ClientMessage::Start { seed: request.seed });
+    }
+}
+
+// also over once the player starts a run on another seed
+#[allow(clippy::too_many_arguments)]
+fn send_race_progress(
+    mut connection: NonSendMut<RaceConnection>,
+    mut race: ResMut<Race>,
+    mut game_over: EventReader<GameOver>,
+    state: Res<State<AppState>>,
+    score_tracker: Res<ScoreTracker>,
+    game_rng: Res<GameRng>,
+    fruits: Query<(&Fruit, &Transform), Without<MarkForDelete>>,
+    time: Res<Time>,
+    mut since_sent: Local<f32>,
+    mut last_sent: Local<Progress>,
+) {
+    let ended = game_over.iter().count() > 0;
+    let Some(seed) = race.seed else {
+        return;
+    };
+    if *state.get() != AppState::InGame {
+        return;
+    }
+
+    let progress = if game_rng.seed != seed {
+        // the board is another run's by now
+        Progress {
+            over: true,
+            ..last_sent.clone()
+        }
+    } else {
+        *since_sent += time.delta_seconds();
+        if !ended && *since_sent < PROGRESS_INTERVAL {
+            return;
+        }
+        Progress {
+            score: score_tracker.score,
+            largest_tier: score_tracker.largest_tier,
+            drops: score_tracker.drops,
+            over: ended,
+            fruits: fruits
+                .iter()
+                .map(|(fruit, transform)| MiniFruit {
+                    tier: fruit.tier,
+                    x: transform.translation.x,
+                    y: transform.translation.y,
+                })
+                .collect(),
+        }
+    };
+    *since_sent = 0.0;
+    connection.send(&ClientMessage::Progress(progress.clone()));
+    if progress.over {
+        race.seed = None;
+    }
+    *last_sent = progress;
+}
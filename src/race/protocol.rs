@@ -0,0 +1,53 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+// RON text frames, like the saves
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ClientMessage {
+    Join { room: String, name: String },
+    // starts a race for everyone in the room, on this seed or one the server picks
+    Start { seed: Option<u64> },
+    Progress(Progress),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ServerMessage {
+    // the reply to joining, with everyone who was already in the room
+    Welcome { id: u32, players: Vec<PlayerInfo> },
+    Joined(PlayerInfo),
+    Left { id: u32 },
+    Started { seed: u64 },
+    Progress { id: u32, progress: Progress },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerInfo {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Progress {
+    pub score: u32,
+    pub largest_tier: usize,
+    pub drops: u32,
+    // the run is over, the score is final
+    pub over: bool,
+    pub fruits: Vec<MiniFruit>,
+}
+
+// container centered on x = 0
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MiniFruit {
+    pub tier: usize,
+    pub x: f32,
+    pub y: f32,
+}
+
+pub fn encode<T: Serialize>(message: &T) -> String {
+    ron::to_string(message).unwrap_or_default()
+}
+
+pub fn decode<T: DeserializeOwned>(text: &str) -> Option<T> {
+    ron::from_str(text).ok()
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use bevy::log::{error, warn};
+use tungstenite::Message;
+
+use crate::resources::random_seed;
+
+use super::connection::is_timeout;
+use super::protocol::{decode, encode, ClientMessage, PlayerInfo, ServerMessage};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+struct Player {
+    info: PlayerInfo,
+    outbox: Sender<ServerMessage>,
+}
+
+#[derive(Default)]
+struct Rooms {
+    rooms: HashMap<String, Vec<Player>>,
+}
+
+impl Rooms {
+    fn send_to_room(&self, room: &str, message: &ServerMessage, except: Option<u32>) {
+        for player in self.rooms.get(room).into_iter().flatten() {
+            if Some(player.info.id) != except {
+                // the connection may be on its way out
+                let _ = player.outbox.send(message.clone());
+            }
+        }
+    }
+}
+
+// a thread per connection, never returns, the runs themselves aren't checked
+pub fn run_relay(listener: TcpListener) {
+    let rooms = Arc::new(Mutex::new(Rooms::default()));
+    let next_id = Arc::new(AtomicU32::new(1));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                error!("could not accept a connection: {}", err);
+                continue;
+            }
+        };
+        let rooms = rooms.clone();
+        let id = next_id.fetch_add(1, Ordering::Relaxed);
+        thread::spawn(move || serve(stream, id, rooms));
+    }
+}
+
+fn serve(stream: TcpStream, id: u32, rooms: Arc<Mutex<Rooms>>) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(err) => {
+            warn!("handshake with player {} failed: {}", id, err);
+            return;
+        }
+    };
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+    let (outbox, inbox) = mpsc::channel();
+    let mut room = None;
+
+    'connection: loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => match decode(&text) {
+                Some(message) => handle(message, id, &mut room, &outbox, &rooms),
+                None => warn!("unexpected message from player {}: {}", id, text),
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err)) if is_timeout(&err) => {}
+            Err(_) => break,
+        }
+        for message in inbox.try_iter() {
+            if socket.send(Message::Text(encode(&message))).is_err() {
+                break 'connection;
+            }
+        }
+    }
+
+    if let Some(room) = room {
+        let mut rooms = rooms.lock().unwrap();
+        if let Some(players) = rooms.rooms.get_mut(&room) {
+            players.retain(|player| player.info.id != id);
+            if players.is_empty() {
+                rooms.rooms.remove(&room);
+            }
+        }
+        rooms.send_to_room(&room, &ServerMessage::Left { id }, None);
+    }
+}
+
+fn handle(
+    message: ClientMessage,
+    id: u32,
+    room: &mut Option<String>,
+    outbox: &Sender<ServerMessage>,
+    rooms: &Mutex<Rooms>,
+) {
+    let mut rooms = rooms.lock().unwrap();
+    match (message, room.clone()) {
+        // a connection stays in the room it joined first
+        (ClientMessage::Join { room: name, .. }, Some(_)) => {
+            warn!("player {} tried to join {} while in a room", id, name);
+        }
+        (
+            ClientMessage::Join {
+                room: name,
+                name: player_name,
+            },
+            None,
+        ) => {
+            let info = PlayerInfo {
+                id,
+                name: player_name,
+            };
+            let players = rooms.rooms.entry(name.clone()).or_default();
+            let _ = outbox.send(ServerMessage::Welcome {
+                id,
+                players: players.iter().map(|player| player.info.clone()).collect(),
+            });
+            players.push(Player {
+                info: info.clone(),
+                outbox: outbox.clone(),
+            });
+            rooms.send_to_room(&name, &ServerMessage::Joined(info), Some(id));
+            *room = Some(name);
+        }
+        (ClientMessage::Start { seed }, Some(name)) => {
+            let seed = seed.unwrap_or_else(random_seed);
+            rooms.send_to_room(&name, &ServerMessage::Started { seed }, None);
+        }
+        (ClientMessage::Progress(progress), Some(name)) => {
+            rooms.send_to_room(&name, &ServerMessage::Progress { id, progress }, Some(id));
+        }
+        // nothing to do before joining a room
+        (_, None) => {}
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{
+    CONTAINER_BASE_OFFSET, CONTAINER_HEIGHT, CONTAINER_THICKNESS, CONTAINER_WIDTH, CONTROLS_RIGHT,
+    NEXT_BG_COLOR, SCORE_TEXT_COLOR, SCREEN_HEIGHT, TEXT_COLOR,
+};
+use crate::gamepad::any_gamepad_just_pressed;
+use crate::layout::Layout;
+use crate::resources::NextSeed;
+use crate::AppState;
+
+use super::protocol::MiniFruit;
+use super::{race_connected, Race, StartRace};
+
+// the opponents' containers are drawn this much smaller than the real one
+const MINI_SCALE: f32 = 0.16;
+const RACE_PANEL_WIDTH: f32 = 120.0;
+
+// G (or north on a gamepad) on the start or game over screen starts a race
+pub struct RaceViewPlugin;
+
+impl Plugin for RaceViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                request_race_start.run_if(
+                    in_state(AppState::StartMenu).or_else(in_state(AppState::GameOverMenu)),
+                ),
+                update_race_panel.run_if(resource_changed::<Race>()),
+            )
+                .run_if(race_connected),
+        );
+    }
+}
+
+#[derive(Component)]
+struct RacePanel;
+
+// the seed typed in the start menu is the race's, if there is one
+fn request_race_start(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    next_seed: Res<NextSeed>,
+    mut start_race: EventWriter<StartRace>,
+) {
+    if keyboard_input.just_pressed(KeyCode::G)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::North])
+    {
+        start_race.send(StartRace {
+            seed: next_seed.seed,
+        });
+    }
+}
+
+fn race_status(race: &Race) -> String {
+    let room = race.room.as_deref().unwrap_or_default();
+    if race.disconnected {
+        return String::from("lost the race server");
+    }
+    match (race.id, race.seed) {
+        (None, _) => format!("joining race room {}", room),
+        (Some(_), Some(_)) => format!("racing in {}", room),
+        (Some(_), None) => format!(
+            "race room {}, {} others here, G starts a race",
+            room,
+            race.opponents.len()
+        ),
+    }
+}
+
+// rebuilt whenever anything about the race changes
+fn update_race_panel(
+    mut commands: Commands,
+    panels: Query<Entity, With<RacePanel>>,
+    race: Res<Race>,
+    asset_server: Res<AssetServer>,
+    catalog: Res<FruitCatalog>,
+) {
+    for panel in panels.iter() {
+        commands.entity(panel).despawn_recursive();
+    }
+
+    let text_style = |color: Color| TextStyle {
+        font_size: 16.0,
+        color,
+        ..default()
+    };
+    commands
+        .spawn((
+            RacePanel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(Layout::menu_control_top(2)),
+                    right: Val::Px(CONTROLS_RIGHT),
+                    width: Val::Px(RACE_PANEL_WIDTH),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section(
+                race_status(&race),
+                text_style(TEXT_COLOR),
+            ));
+            for opponent in race.opponents.iter() {
+                let progress = &opponent.progress;
+                let finished = if progress.over { " (over)" } else { "" };
+                builder.spawn(TextBundle::from_section(
+                    format!("{}: {}{}", opponent.name, progress.score, finished),
+                    text_style(SCORE_TEXT_COLOR),
+                ));
+                spawn_mini_container(builder, &progress.fruits, &asset_server, &catalog);
+            }
+        });
+}
+
+fn spawn_mini_container(
+    builder: &mut ChildBuilder,
+    fruits: &[MiniFruit],
+    asset_server: &AssetServer,
+    catalog: &FruitCatalog,
+) {
+    let floor = -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET + CONTAINER_THICKNESS / 2.0;
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(CONTAINER_WIDTH * MINI_SCALE),
+                height: Val::Px(CONTAINER_HEIGHT * MINI_SCALE),
+                ..default()
+            },
+            background_color: NEXT_BG_COLOR.into(),
+            ..default()
+        })
+        .with_children(|builder| {
+            for fruit in fruits {
+                // the opponent may be playing a longer roster
+                let Some(kind) = catalog.fruits.get(fruit.tier) else {
+                    continue;
+                };
+                let size = kind.size * MINI_SCALE;
+                builder.spawn(ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((fruit.x + CONTAINER_WIDTH / 2.0) * MINI_SCALE - size / 2.0),
+                        bottom: Val::Px((fruit.y - floor) * MINI_SCALE - size / 2.0),
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load(&kind.texture)),
+                    ..default()
+                });
+            }
+        });
+}
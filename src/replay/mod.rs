@@ -0,0 +1,264 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{SCREEN_HEIGHT, TEXT_COLOR};
+use crate::menu::MenuItem;
+use crate::resources::{GameMode, NextSeed, RunClock, ScoreTracker, ScoringRules};
+use crate::simulation::{FruitDropped, FruitHeld, RunStarted, SimulationSet};
+use crate::AppState;
+
+mod playback;
+pub use playback::ReplayPlayback;
+use playback::{
+    check_replay_sync, cleanup_playback, play_back_drops, replay_controls, setup_playback,
+    update_replay_text,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_systems(Startup, start_replay_from_args)
+            // right after the simulation, so each drop gets the step it happened on
+            .add_systems(
+                FixedUpdate,
+                (start_recording, record_drops.after(start_recording))
+                    .after(SimulationSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOverMenu),
+                (save_replay, setup_watch_hint),
+            )
+            .add_systems(
+                Update,
+                watch_last_replay.run_if(in_state(AppState::GameOverMenu)),
+            )
+            .add_systems(OnEnter(AppState::Replay), setup_playback)
+            .add_systems(
+                FixedUpdate,
+                (
+                    play_back_drops.before(SimulationSet),
+                    check_replay_sync.after(SimulationSet),
+                )
+                    .run_if(in_state(AppState::Replay)),
+            )
+            .add_systems(
+                Update,
+                (replay_controls, update_replay_text).run_if(in_state(AppState::Replay)),
+            )
+            .add_systems(OnExit(AppState::Replay), cleanup_playback);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Replay {
+    pub seed: u64,
+    pub drops: Vec<ReplayDrop>,
+    pub rules: ScoringRules,
+    pub holds: Vec<ReplayHold>,
+    pub mode: GameMode,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayDrop {
+    // fixed step of the run the drop happened on, counting from 1
+    pub frame: u32,
+    pub x: f32,
+    // both checked on playback
+    pub tier: usize,
+    pub score: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayHold {
+    // drops before the hold, which keeps the fruit queue in sync
+    pub drops: usize,
+    pub frame: u32,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not access replay file: {}", err),
+            ReplayError::Parse(err) => write!(f, "invalid replay file: {}", err),
+            ReplayError::Serialize(err) => write!(f, "could not serialize replay: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn new(seed: u64, rules: ScoringRules, mode: GameMode) -> Self {
+        Self {
+            seed,
+            drops: Vec::new(),
+            rules,
+            holds: Vec::new(),
+            mode,
+        }
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, ReplayError> {
+        ron::from_str(ron).map_err(ReplayError::Parse)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ReplayError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ReplayError::Serialize)
+    }
+
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        Self::from_ron(&std::fs::read_to_string(path).map_err(ReplayError::Io)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_ron()?).map_err(ReplayError::Io)
+    }
+}
+
+// the current run, or the last one that finished
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+}
+
+fn start_recording(
+    mut run_started: EventReader<RunStarted>,
+    mut recorder: ResMut<ReplayRecorder>,
+    score_tracker: Res<ScoreTracker>,
+) {
+    for started in run_started.iter() {
+        recorder.replay = Replay::new(started.seed, score_tracker.rules, score_tracker.mode);
+    }
+}
+
+// holds first, like the simulation does on a step with both
+fn record_drops(
+    mut fruit_held: EventReader<FruitHeld>,
+    mut fruit_dropped: EventReader<FruitDropped>,
+    run_clock: Res<RunClock>,
+    score_tracker: Res<ScoreTracker>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    for _ in fruit_held.iter() {
+        let drops = recorder.replay.drops.len();
+        recorder.replay.holds.push(ReplayHold {
+            drops,
+            frame: run_clock.frame,
+        });
+    }
+    for dropped in fruit_dropped.iter() {
+        recorder.replay.drops.push(ReplayDrop {
+            frame: run_clock.frame,
+            x: dropped.x,
+            tier: dropped.fruit.tier,
+            score: score_tracker.score,
+        });
+    }
+}
+
+// only written to disk natively
+fn save_replay(recorder: Res<ReplayRecorder>) {
+    if recorder.replay.drops.is_empty() {
+        return;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = replay_path(recorder.replay.seed);
+        let saved = std::fs::create_dir_all("replays")
+            .map_err(ReplayError::Io)
+            .and_then(|_| recorder.replay.save(&path));
+        match saved {
+            Ok(()) => info!("replay saved to {}", path),
+            Err(err) => warn!("{}", err),
+        }
+    }
+}
+
+// the time keeps replays of the same seed apart
+#[cfg(not(target_arch = "wasm32"))]
+fn replay_path(seed: u64) -> String {
+    let base = format!(
+        "replays/replay-{}-{}",
+        seed,
+        crate::storage::now_unix_secs()
+    );
+    let mut path = format!("{}.ron", base);
+    let mut count = 1;
+    while std::path::Path::new(&path).exists() {
+        count += 1;
+        path = format!("{}-{}.ron", base, count);
+    }
+    path
+}
+
+// `suika --replay <file>` starts straight into playback
+fn start_replay_from_args(
+    mut commands: Commands,
+    mut next_seed: ResMut<NextSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let args: Vec<String> = std::env::args().collect();
+    let replay_path = args
+        .iter()
+        .position(|arg| arg == "--replay")
+        .and_then(|i| args.get(i + 1));
+    if let Some(path) = replay_path {
+        match Replay::load(path) {
+            Ok(replay) => {
+                next_seed.seed = Some(replay.seed);
+                commands.insert_resource(ReplayPlayback::new(replay));
+                next_state.set(AppState::Replay);
+            }
+            Err(err) => error!("{}", err),
+        }
+    }
+}
+
+fn setup_watch_hint(mut commands: Commands, recorder: Res<ReplayRecorder>) {
+    if recorder.replay.drops.is_empty() {
+        return;
+    }
+    commands.spawn((
+        MenuItem,
+        Text2dBundle {
+            text: Text::from_section(
+                "press R to watch the replay",
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0 - 290.0, 1.0),
+            ..default()
+        },
+    ));
+}
+
+fn watch_last_replay(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    recorder: Res<ReplayRecorder>,
+    mut next_seed: ResMut<NextSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::R) && !recorder.replay.drops.is_empty() {
+        next_seed.seed = Some(recorder.replay.seed);
+        commands.insert_resource(ReplayPlayback::new(recorder.replay.clone()));
+        next_state.set(AppState::Replay);
+    }
+}
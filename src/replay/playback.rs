@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{SCREEN_HEIGHT, TEXT_COLOR};
+use crate::resources::{NextGenerator, RunClock, ScoreTracker};
+use crate::setup::Preview;
+use crate::simulation::{pos_x_in_bounds, DropFruit, FruitDropped, HoldFruit};
+use crate::AppState;
+
+use super::Replay;
+
+const SPEEDS: [(KeyCode, f32); 3] = [
+    (KeyCode::Key1, 1.0),
+    (KeyCode::Key2, 2.0),
+    (KeyCode::Key4, 4.0),
+];
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    next_drop: usize,
+    next_hold: usize,
+    played_drops: usize,
+    in_sync: bool,
+    speed: f32,
+    paused: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_drop: 0,
+            next_hold: 0,
+            played_drops: 0,
+            in_sync: true,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next_drop >= self.replay.drops.len() && self.next_hold >= self.replay.holds.len()
+    }
+
+    pub fn in_sync(&self) -> bool {
+        self.in_sync
+    }
+}
+
+#[derive(Component)]
+pub struct ReplayItem;
+
+#[derive(Component)]
+pub struct ReplayText;
+
+pub fn setup_playback(
+    mut commands: Commands,
+    playback: Res<ReplayPlayback>,
+    mut score_tracker: ResMut<ScoreTracker>,
+) {
+    // the run began with the player's rules and mode, not the replay's
+    score_tracker.rules = playback.replay.rules;
+    score_tracker.mode = playback.replay.mode;
+    commands.spawn((
+        ReplayItem,
+        ReplayText,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0 - 30.0, 1.0),
+            ..default()
+        },
+    ));
+}
+
+pub fn cleanup_playback(
+    mut commands: Commands,
+    replay_items: Query<Entity, With<ReplayItem>>,
+    mut time: ResMut<Time>,
+) {
+    for replay_item in replay_items.iter() {
+        commands.entity(replay_item).despawn_recursive();
+    }
+    commands.remove_resource::<ReplayPlayback>();
+    time.set_relative_speed(1.0);
+    time.unpause();
+}
+
+// sent for the step the simulation is about to run
+pub fn play_back_drops(
+    mut playback: ResMut<ReplayPlayback>,
+    run_clock: Res<RunClock>,
+    next_generator: Res<NextGenerator>,
+    mut hold_requests: EventWriter<HoldFruit>,
+    mut drop_requests: EventWriter<DropFruit>,
+    mut preview: Query<&mut Transform, With<Preview>>,
+    catalog: Res<FruitCatalog>,
+) {
+    let frame = run_clock.frame + 1;
+    if let Some(hold) = playback.replay.holds.get(playback.next_hold) {
+        if hold.drops == playback.next_drop && hold.frame <= frame {
+            hold_requests.send(HoldFruit);
+            playback.next_hold += 1;
+        }
+    }
+
+    let Some(drop) = playback.replay.drops.get(playback.next_drop) else {
+        return;
+    };
+    if drop.frame > frame {
+        return;
+    }
+    if let Ok(mut transform) = preview.get_single_mut() {
+        let size = catalog.kind(&next_generator.current_fruit).size;
+        transform.translation.x = pos_x_in_bounds(drop.x, size);
+    }
+    drop_requests.send(DropFruit { x: drop.x });
+    playback.next_drop += 1;
+}
+
+pub fn check_replay_sync(
+    mut playback: ResMut<ReplayPlayback>,
+    mut fruit_dropped: EventReader<FruitDropped>,
+    score_tracker: Res<ScoreTracker>,
+) {
+    for dropped in fruit_dropped.iter() {
+        let index = playback.played_drops;
+        playback.played_drops += 1;
+        let Some(recorded) = playback.replay.drops.get(index) else {
+            continue;
+        };
+        if !playback.in_sync {
+            continue;
+        }
+        if recorded.tier != dropped.fruit.tier {
+            warn!(
+                "replay out of sync at drop {}: recorded tier {} but dropped tier {}",
+                index + 1,
+                recorded.tier,
+                dropped.fruit.tier
+            );
+            playback.in_sync = false;
+        } else if recorded.score != score_tracker.score {
+            warn!(
+                "replay out of sync at drop {}: recorded a score of {} but got {}",
+                index + 1,
+                recorded.score,
+                score_tracker.score
+            );
+            playback.in_sync = false;
+        }
+    }
+}
+
+// speed and pause only change how much time the fixed step gets to catch up on
+pub fn replay_controls(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time: ResMut<Time>,
+    mut fixed_time: ResMut<FixedTime>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (key, speed) in SPEEDS {
+        if keyboard_input.just_pressed(key) {
+            playback.speed = speed;
+            time.set_relative_speed(speed);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+        if playback.paused {
+            time.pause();
+        } else {
+            time.unpause();
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Period) && playback.paused {
+        let period = fixed_time.period;
+        fixed_time.tick(period);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::GameOverMenu);
+    }
+}
+
+pub fn update_replay_text(
+    playback: Res<ReplayPlayback>,
+    mut replay_text: Query<&mut Text, With<ReplayText>>,
+) {
+    if let Ok(mut text) = replay_text.get_single_mut() {
+        let mut status = if playback.paused {
+            String::from("paused")
+        } else {
+            format!("{}x", playback.speed)
+        };
+        if !playback.in_sync {
+            status.push_str(", out of sync");
+        }
+        text.sections[0].value = format!(
+            "replay {} - drop {}/{}   1/2/4: speed   space: pause   .: step   esc: stop",
+            status,
+            playback.next_drop,
+            playback.replay.drops.len()
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use bevy::prelude::*;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    constants::{ALIVE_MASS_MIN, CLICK_DELAY, KNOWN_TYPES, MASS},
-    Fruit,
+    catalog::FruitCatalog,
+    constants::{
+        ALIVE_MASS_MIN, CLICK_DELAY, GAME_OVER_GRACE, MASS, TIME_ATTACK_CLICK_DELAY,
+        TIME_ATTACK_LIMIT, TOUCH_MOUSE_GRACE,
+    },
+    storage, Fruit,
 };
 
 #[derive(Resource, Default)]
@@ -12,7 +18,7 @@ pub struct GameAlreadySetUp {
     pub is_set_up: bool,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Component)]
 pub struct SpawnTime {
     // prevent spawning in quick succession
     pub timer: Timer,
@@ -28,14 +34,97 @@ impl Default for SpawnTime {
 }
 
 impl SpawnTime {
-    pub fn start_new_timer(&mut self) {
-        self.timer = Timer::from_seconds(CLICK_DELAY, TimerMode::Once);
+    pub fn start_new_timer(&mut self, mode: GameMode) {
+        self.timer = Timer::from_seconds(mode.spawn_cooldown(), TimerMode::Once);
+    }
+}
+
+#[derive(Resource)]
+pub struct GameOverGrace {
+    pub seconds: f32,
+}
+
+impl Default for GameOverGrace {
+    fn default() -> Self {
+        Self {
+            seconds: GAME_OVER_GRACE,
+        }
+    }
+}
+
+// None while the pile is below the game over line
+#[derive(Resource, Component, Default)]
+pub struct DangerCountdown {
+    pub elapsed: Option<f32>,
+}
+
+impl DangerCountdown {
+    pub fn remaining(&self, grace: &GameOverGrace) -> Option<f32> {
+        self.elapsed
+            .map(|elapsed| (grace.seconds - elapsed).max(0.0))
+    }
+}
+
+// shared by every input method
+#[derive(Resource, Component, Default)]
+pub struct Aim {
+    pub x: f32,
+}
+
+// browsers follow a tap with emulated mouse events, which must not count as a second press
+#[derive(Resource)]
+pub struct RecentTouch {
+    pub timer: Timer,
+}
+
+impl Default for RecentTouch {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.0, TimerMode::Once),
+        }
     }
 }
 
+impl RecentTouch {
+    pub fn touched(&mut self) {
+        self.timer = Timer::from_seconds(TOUCH_MOUSE_GRACE, TimerMode::Once);
+    }
+
+    pub fn is_recent(&self) -> bool {
+        !self.timer.finished()
+    }
+}
+
+// frozen whenever the simulation isn't running
 #[derive(Resource, Default)]
+pub struct RunClock {
+    pub elapsed: f32,
+    pub frame: u32,
+}
+
+impl RunClock {
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+        self.frame += 1;
+    }
+}
+
+// each side of a versus match has its own as a component
+#[derive(Resource, Component, Default)]
 pub struct ScoreTracker {
     pub score: u32,
+    pub largest_tier: usize,
+    // of the current run, the chosen ones only apply to the next
+    pub rules: ScoringRules,
+    pub mode: GameMode,
+    pub drops: u32,
+    // longest chain of merges, and how many merges were part of a chain
+    pub best_combo: u32,
+    pub combo_merges: u32,
+    // such runs don't make the high score tables
+    pub autoplayed: bool,
+    // zen runs go on the table once, on a restart or when quitting
+    pub recorded: bool,
 }
 
 impl ScoreTracker {
@@ -43,8 +132,122 @@ impl ScoreTracker {
         self.score += s;
     }
 
+    // chain is 1 for a merge on its own
+    pub fn add_merge_score(&mut self, s: u32, chain: u32) -> u32 {
+        let points = match self.rules {
+            ScoringRules::Classic => s,
+            ScoringRules::Combo => {
+                self.best_combo = self.best_combo.max(chain);
+                if chain > 1 {
+                    self.combo_merges += 1;
+                }
+                s * chain
+            }
+        };
+        self.add_score(points);
+        points
+    }
+
+    pub fn reached_tier(&mut self, tier: usize) {
+        self.largest_tier = self.largest_tier.max(tier);
+    }
+
     pub fn reset(&mut self) {
         self.score = 0;
+        self.largest_tier = 0;
+        self.drops = 0;
+        self.best_combo = 0;
+        self.combo_merges = 0;
+        self.autoplayed = false;
+    }
+}
+
+// the player's choice applies from the next run on
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ScoringRules {
+    // each merge is worth the score of the new fruit
+    #[default]
+    Classic,
+    // multiplied by the length of the chain
+    Combo,
+}
+
+impl ScoringRules {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            ScoringRules::Classic => ScoringRules::Combo,
+            ScoringRules::Combo => ScoringRules::Classic,
+        };
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    // highest score before the time runs out
+    TimeAttack,
+    // the largest fruit in as few drops as possible
+    Target,
+    // no game over, the run lasts until it's restarted
+    Zen,
+    // everyone gets the same queue that day
+    Daily,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::TimeAttack,
+        GameMode::Target,
+        GameMode::Zen,
+        GameMode::Daily,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::TimeAttack => "time attack",
+            GameMode::Target => "target",
+            GameMode::Zen => "zen",
+            GameMode::Daily => "daily",
+        }
+    }
+
+    pub fn fresh_seed(&self) -> u64 {
+        match self {
+            GameMode::Daily => daily_seed(storage::now_unix_secs()),
+            _ => random_seed(),
+        }
+    }
+
+    pub fn cycle(&self, steps: isize) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+        Self::ALL[(index + steps).rem_euclid(len) as usize]
+    }
+
+    pub fn has_game_over(&self) -> bool {
+        *self != GameMode::Zen
+    }
+
+    pub fn time_limit(&self) -> Option<f32> {
+        match self {
+            GameMode::TimeAttack => Some(TIME_ATTACK_LIMIT),
+            _ => None,
+        }
+    }
+
+    pub fn spawn_cooldown(&self) -> f32 {
+        match self {
+            GameMode::TimeAttack => TIME_ATTACK_CLICK_DELAY,
+            _ => CLICK_DELAY,
+        }
+    }
+
+    // fewer is better
+    pub fn ranks_by_drops(&self) -> bool {
+        *self == GameMode::Target
     }
 }
 
@@ -82,50 +285,214 @@ impl NoiseSetting {
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
+pub struct ReducedMotion {
+    pub is_on: bool,
+}
+
+impl ReducedMotion {
+    pub fn toggle(&mut self) {
+        self.is_on = !self.is_on;
+    }
+}
+
+// all the randomness of a run, the same seed and inputs give the same game
+#[derive(Resource, Component)]
+pub struct GameRng {
+    pub seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(random_seed())
+    }
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::from_seed(seed);
+    }
+
+    pub fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    // together with the seed this is the whole rng state
+    pub fn word_pos(&self) -> u64 {
+        self.rng.get_word_pos() as u64
+    }
+
+    pub fn restore(seed: u64, word_pos: u64) -> Self {
+        let mut game_rng = Self::from_seed(seed);
+        game_rng.rng.set_word_pos(word_pos as u128);
+        game_rng
+    }
+}
+
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+// the UTC date as a number, e.g. 20231024
+pub fn daily_seed(unix_secs: u64) -> u64 {
+    let (year, month, day) = storage::utc_date(unix_secs);
+    year as u64 * 10_000 + month as u64 * 100 + day as u64
+}
+
+pub fn daily_seed_date(seed: u64) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        seed / 10_000,
+        seed / 100 % 100,
+        seed % 100
+    )
+}
+
+// random if not set
+#[derive(Resource, Default)]
+pub struct NextSeed {
+    pub seed: Option<u64>,
+}
+
+#[derive(Resource, Component)]
 pub struct NextGenerator {
     pub current_fruit: Fruit,
     pub next_fruit: Fruit,
+    pub held_fruit: Option<Fruit>,
+    // the hold can be used once per drop
+    pub hold_used: bool,
     pub should_update_previews: bool,
 }
 
-impl Default for NextGenerator {
-    fn default() -> Self {
-        let mut rng = rand::thread_rng();
-        let (cur, next) = (rng.gen_range(0..5), rng.gen_range(0..5));
-        let (cur_size, file_name, cur_score) = KNOWN_TYPES[cur];
-        let (next_size, next_file_name, next_score) = KNOWN_TYPES[next];
-        Self {
-            current_fruit: Fruit {
-                size: cur_size,
-                file_name: String::from(file_name),
-                score: cur_score,
-            },
-            next_fruit: Fruit {
-                size: next_size,
-                file_name: String::from(next_file_name),
-                score: next_score,
-            },
-            should_update_previews: false,
-        }
+impl FromWorld for NextGenerator {
+    fn from_world(world: &mut World) -> Self {
+        let catalog = world
+            .get_resource_or_insert_with(FruitCatalog::default)
+            .clone();
+        let mut game_rng = world.get_resource_or_insert_with(GameRng::default);
+        Self::new(&mut game_rng, &catalog)
     }
 }
 
 impl NextGenerator {
-    pub fn next(&mut self) {
-        let mut rng = rand::thread_rng();
-        let next = rng.gen_range(0..5);
-        self.current_fruit = self.next_fruit.clone();
-        let (size, file_name, score) = KNOWN_TYPES[next];
-        self.next_fruit = Fruit {
-            size,
-            file_name: String::from(file_name),
-            score,
-        };
+    pub fn new(game_rng: &mut GameRng, catalog: &FruitCatalog) -> Self {
+        Self {
+            current_fruit: Self::random_fruit(game_rng, catalog),
+            next_fruit: Self::random_fruit(game_rng, catalog),
+            held_fruit: None,
+            hold_used: false,
+            should_update_previews: true,
+        }
+    }
+
+    pub fn next(&mut self, game_rng: &mut GameRng, catalog: &FruitCatalog) {
+        self.current_fruit = self.next_fruit;
+        self.next_fruit = Self::random_fruit(game_rng, catalog);
+        self.hold_used = false;
         self.should_update_previews = true;
     }
 
+    // false if the hold was already used since the last drop
+    pub fn hold(&mut self, game_rng: &mut GameRng, catalog: &FruitCatalog) -> bool {
+        if self.hold_used {
+            return false;
+        }
+        let held = self.current_fruit;
+        match self.held_fruit.replace(held) {
+            Some(fruit) => self.current_fruit = fruit,
+            None => self.next(game_rng, catalog),
+        }
+        self.hold_used = true;
+        self.should_update_previews = true;
+        true
+    }
+
     pub fn preview_updated(&mut self) {
         self.should_update_previews = false;
     }
+
+    fn random_fruit(game_rng: &mut GameRng, catalog: &FruitCatalog) -> Fruit {
+        Fruit {
+            tier: game_rng.rng().gen_range(0..catalog.droppable),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fruit_queue(game_rng: &mut GameRng, catalog: &FruitCatalog, count: usize) -> Vec<usize> {
+        let mut next_generator = NextGenerator::new(game_rng, catalog);
+        (0..count)
+            .map(|_| {
+                let tier = next_generator.current_fruit.tier;
+                next_generator.next(game_rng, catalog);
+                tier
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_fruits() {
+        let catalog = FruitCatalog::default();
+        let first = fruit_queue(&mut GameRng::from_seed(7), &catalog, 50);
+        let second = fruit_queue(&mut GameRng::from_seed(7), &catalog, 50);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|tier| *tier < catalog.droppable));
+
+        let other = fruit_queue(&mut GameRng::from_seed(8), &catalog, 50);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn reseeding_starts_the_stream_over() {
+        let catalog = FruitCatalog::default();
+        let mut game_rng = GameRng::from_seed(7);
+        let first = fruit_queue(&mut game_rng, &catalog, 20);
+        game_rng.reseed(7);
+        assert_eq!(game_rng.word_pos(), 0);
+        assert_eq!(fruit_queue(&mut game_rng, &catalog, 20), first);
+    }
+
+    #[test]
+    fn restore_continues_the_stream() {
+        let mut game_rng = GameRng::from_seed(1234);
+        for _ in 0..37 {
+            game_rng.rng().gen::<u32>();
+        }
+        // a u64 takes two words, so the position isn't just the number of draws
+        game_rng.rng().gen::<u64>();
+        let mut restored = GameRng::restore(game_rng.seed, game_rng.word_pos());
+        assert_eq!(restored.seed, 1234);
+        assert_eq!(restored.word_pos(), game_rng.word_pos());
+
+        let expected: Vec<u32> = (0..100).map(|_| game_rng.rng().gen()).collect();
+        let continued: Vec<u32> = (0..100).map(|_| restored.rng().gen()).collect();
+        assert_eq!(continued, expected);
+    }
+
+    #[test]
+    fn daily_seed_is_the_utc_date() {
+        // 2024-02-29 12:00
+        assert_eq!(daily_seed(1_709_208_000), 20240229);
+        assert_eq!(daily_seed_date(20240229), "2024-02-29");
+    }
+
+    #[test]
+    fn daily_seed_changes_at_midnight_utc() {
+        assert_eq!(daily_seed(1_704_067_199), 20231231);
+        assert_eq!(daily_seed(1_704_067_200), 20240101);
+        assert_eq!(daily_seed(1_706_745_599), 20240131);
+        assert_eq!(daily_seed(1_706_745_600), 20240201);
+        assert_eq!(daily_seed_date(20240101), "2024-01-01");
+    }
 }
@@ -0,0 +1,146 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::FruitCatalog;
+use crate::constants::TEXT_COLOR;
+use crate::menu::MenuItem;
+use crate::replay::{Replay, ReplayRecorder};
+use crate::resources::NextSeed;
+use crate::simulation::{capture_board, BoardSnapshot, PendingRestore};
+use crate::storage;
+use crate::AppState;
+
+const STORAGE_KEY: &str = "saved_run";
+const AUTOSAVE_INTERVAL: f32 = 2.0;
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedRunSlot {
+            run: SavedRun::load(),
+        })
+        .add_systems(OnEnter(AppState::StartMenu), setup_continue_hint)
+        .add_systems(
+            Update,
+            continue_saved_run.run_if(in_state(AppState::StartMenu)),
+        )
+        .add_systems(Update, autosave.run_if(in_state(AppState::InGame)))
+        .add_systems(OnEnter(AppState::Paused), save_current_run)
+        .add_systems(
+            Last,
+            save_on_exit.run_if(in_state(AppState::InGame).or_else(in_state(AppState::Paused))),
+        )
+        .add_systems(OnEnter(AppState::GameOverMenu), clear_saved_run);
+    }
+}
+
+// the replay so far too, so the finished run still has all of it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedRun {
+    pub board: BoardSnapshot,
+    pub replay: Replay,
+}
+
+impl SavedRun {
+    pub fn load() -> Option<Self> {
+        storage::load_ron(STORAGE_KEY)
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+
+    pub fn clear() {
+        storage::remove(STORAGE_KEY);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SavedRunSlot {
+    pub run: Option<SavedRun>,
+}
+
+fn setup_continue_hint(mut commands: Commands, slot: Res<SavedRunSlot>) {
+    if slot.run.is_none() {
+        return;
+    }
+    commands.spawn((
+        MenuItem,
+        Text2dBundle {
+            text: Text::from_section(
+                "press C to continue your last run",
+                TextStyle {
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(0.0, 50.0, 0.0),
+            ..default()
+        },
+    ));
+}
+
+// a save from another roster is thrown away for a fresh run
+fn continue_saved_run(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut slot: ResMut<SavedRunSlot>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut next_seed: ResMut<NextSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    catalog: Res<FruitCatalog>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::C) {
+        return;
+    }
+    let Some(saved) = slot.run.take() else {
+        return;
+    };
+    if saved.board.fits(&catalog) {
+        // the seed comes from the saved run
+        next_seed.seed = None;
+        recorder.replay = saved.replay;
+        commands.insert_resource(PendingRestore(saved.board));
+    } else {
+        warn!("the saved run has fruits the current roster doesn't, starting a new run");
+        SavedRun::clear();
+    }
+    next_state.set(AppState::InGame);
+}
+
+fn save_current_run(world: &mut World) {
+    // nothing worth continuing before the first drop
+    if world.resource::<ReplayRecorder>().replay.drops.is_empty() {
+        SavedRun::clear();
+        return;
+    }
+    SavedRun {
+        board: capture_board(world),
+        replay: world.resource::<ReplayRecorder>().replay.clone(),
+    }
+    .save();
+}
+
+fn autosave(world: &mut World, mut timer: Local<Option<Timer>>) {
+    let delta = world.resource::<Time>().delta();
+    let timer =
+        timer.get_or_insert_with(|| Timer::from_seconds(AUTOSAVE_INTERVAL, TimerMode::Repeating));
+    if timer.tick(delta).just_finished() {
+        save_current_run(world);
+    }
+}
+
+fn save_on_exit(world: &mut World) {
+    if !world.resource::<Events<AppExit>>().is_empty() {
+        save_current_run(world);
+    }
+}
+
+fn clear_saved_run(mut slot: ResMut<SavedRunSlot>) {
+    slot.run = None;
+    SavedRun::clear();
+}
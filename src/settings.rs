@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{
+    GameMode, GameOverGrace, MassSetting, NoiseSetting, ReducedMotion, ScoringRules,
+};
+use crate::storage;
+
+const STORAGE_KEY: &str = "settings";
+// wait for the slider to stop moving before writing anything
+const SAVE_DELAY: f32 = 0.5;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let stored = StoredSettings::load();
+        app.insert_resource(MassSetting {
+            percentage: stored.physics,
+        })
+        .insert_resource(NoiseSetting {
+            is_on: stored.noise,
+        })
+        .insert_resource(ReducedMotion {
+            is_on: stored.reduced_motion,
+        })
+        .insert_resource(stored.scoring)
+        .insert_resource(stored.mode)
+        .insert_resource(GameOverGrace {
+            seconds: stored.game_over_grace,
+        })
+        .add_systems(Update, save_changed_settings);
+    }
+}
+
+// missing fields fall back to their defaults
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoredSettings {
+    pub physics: f32,
+    pub noise: bool,
+    pub reduced_motion: bool,
+    pub scoring: ScoringRules,
+    pub mode: GameMode,
+    // only changeable by editing the file
+    pub game_over_grace: f32,
+}
+
+impl Default for StoredSettings {
+    fn default() -> Self {
+        Self {
+            physics: MassSetting::default().percentage,
+            noise: NoiseSetting::default().is_on,
+            reduced_motion: ReducedMotion::default().is_on,
+            scoring: ScoringRules::default(),
+            mode: GameMode::default(),
+            game_over_grace: GameOverGrace::default().seconds,
+        }
+    }
+}
+
+impl StoredSettings {
+    pub fn load() -> Self {
+        storage::load_ron(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save_ron(STORAGE_KEY, self);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_changed_settings(
+    mass_setting: Res<MassSetting>,
+    noise_setting: Res<NoiseSetting>,
+    reduced_motion: Res<ReducedMotion>,
+    scoring: Res<ScoringRules>,
+    mode: Res<GameMode>,
+    grace: Res<GameOverGrace>,
+    time: Res<Time>,
+    mut save_timer: Local<Option<Timer>>,
+) {
+    // being inserted at startup isn't a change worth saving
+    if (mass_setting.is_changed() && !mass_setting.is_added())
+        || (noise_setting.is_changed() && !noise_setting.is_added())
+        || (reduced_motion.is_changed() && !reduced_motion.is_added())
+        || (scoring.is_changed() && !scoring.is_added())
+        || (mode.is_changed() && !mode.is_added())
+    {
+        *save_timer = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+
+    if let Some(timer) = save_timer.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            StoredSettings {
+                physics: mass_setting.percentage,
+                noise: noise_setting.is_on,
+                reduced_motion: reduced_motion.is_on,
+                scoring: *scoring,
+                mode: *mode,
+                game_over_grace: grace.seconds,
+            }
+            .save();
+            *save_timer = None;
+        }
+    }
+}
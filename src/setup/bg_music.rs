@@ -30,6 +30,23 @@ pub fn setup_music(
     ));
 }
 
+pub fn pause_music(music_controller: Query<&AudioSink, With<BgAudio>>) {
+    if let Ok(sink) = music_controller.get_single() {
+        sink.pause();
+    }
+}
+
+pub fn resume_music(
+    noise_setting: Res<NoiseSetting>,
+    music_controller: Query<&AudioSink, With<BgAudio>>,
+) {
+    if let Ok(sink) = music_controller.get_single() {
+        if noise_setting.is_on {
+            sink.play();
+        }
+    }
+}
+
 pub fn on_music_setting_change(
     noise_setting: Res<NoiseSetting>,
     music_controller: Query<&AudioSink, With<BgAudio>>,
@@ -1,37 +1,49 @@
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
 
 use crate::{
+    catalog::FruitCatalog,
     constants::{
         CONTAINER_BASE_OFFSET, CONTAINER_COLOR, CONTAINER_HEIGHT, CONTAINER_THICKNESS,
-        CONTAINER_WIDTH, GAME_OVER_HEIGHT, GAME_OVER_HINT_COLOR, KNOWN_TYPES, NEXT_BG_COLOR,
-        NEXT_PREVIEW_LABEL_SIZE, NEXT_PREVIEW_OFFSET, PREVIEW_HINT_COLOR, SCORE_TEXT_COLOR,
-        SCREEN_HEIGHT, SCREEN_WIDTH, SPAWN_HEIGHT, SPAWN_OFFSET, TEXT_COLOR, TRANSPARENT,
+        CONTAINER_WIDTH, DANGER_COLOR, GAME_OVER_HEIGHT, GAME_OVER_HINT_COLOR, NEXT_BG_COLOR,
+        NEXT_PREVIEW_FRUIT_SIZE, NEXT_PREVIEW_LABEL_SIZE, PREVIEW_HINT_COLOR, PREVIEW_PANEL_HEIGHT,
+        PREVIEW_PANEL_WIDTH, SCORE_TEXT_COLOR, SCREEN_HEIGHT, SPAWN_HEIGHT, SPAWN_OFFSET,
+        TEXT_COLOR, TRANSPARENT,
     },
+    layout::Layout,
     resources::{GameAlreadySetUp, NextGenerator, ScoreTracker},
+    simulation::simulation_running,
     AppState,
 };
 
 mod bg_music;
-use bg_music::{on_music_setting_change, setup_music};
+use bg_music::{on_music_setting_change, pause_music, resume_music, setup_music};
 
 // TODO: refactor this is_game_set_up nonsense by actually putting those setups into Startup schedule
 pub struct SetupPlugin;
 
 impl Plugin for SetupPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (setup_camera, setup_app_boundaries, setup_music))
+        app.add_systems(Startup, (setup_camera, setup_music))
             .add_systems(
                 OnEnter(AppState::InGame),
                 (setup_container, setup_merge_guide, setup_score),
             )
+            .add_systems(
+                OnEnter(AppState::Replay),
+                (setup_container, setup_merge_guide, setup_score),
+            )
+            .add_systems(Update, mark_game_set_up.run_if(simulation_running))
             .add_systems(
                 Update,
                 on_music_setting_change.run_if(in_state(AppState::InGame)),
             )
+            .add_systems(OnEnter(AppState::Paused), pause_music)
+            .add_systems(OnExit(AppState::Paused), resume_music)
             // unlike the other setups, previews are thrown out in GameOver state, and recreated after starting over
             .add_systems(OnEnter(AppState::InGame), setup_preview)
-            .add_systems(OnExit(AppState::InGame), cleanup_preview);
+            .add_systems(OnEnter(AppState::GameOverMenu), cleanup_preview)
+            .add_systems(OnEnter(AppState::Replay), setup_preview)
+            .add_systems(OnExit(AppState::Replay), cleanup_preview);
     }
 }
 
@@ -51,32 +63,94 @@ pub struct PreviewPart;
 #[derive(Component)]
 pub struct Score;
 
+#[derive(Component)]
+pub struct ModeStatus;
+
+#[derive(Component)]
+pub struct GameOverLine;
+
+#[derive(Component)]
+pub struct DangerText;
+
+#[derive(Component)]
+pub struct NextPanel;
+
+#[derive(Component)]
+pub struct HoldPreview;
+
+#[derive(Component)]
+pub struct HoldPanel;
+
+#[derive(Component)]
+pub struct ScorePanel;
+
+#[derive(Component)]
+pub struct MergeGuideEntry {
+    pub index: usize,
+    pub size: f32,
+}
+
+// resuming from a pause re-enters InGame
+fn mark_game_set_up(mut game_already_set_up: ResMut<GameAlreadySetUp>) {
+    if !game_already_set_up.is_set_up {
+        game_already_set_up.is_set_up = true;
+    }
+}
+
 fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadySetUp>) {
     if game_already_set_up.is_set_up {
         return;
     }
 
-    commands.spawn(SpriteBundle {
+    commands
+        .spawn((GameOverLine, game_over_line_bundle(0.0)))
+        .with_children(|builder| {
+            builder.spawn((
+                DangerText,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 30.0,
+                            color: DANGER_COLOR,
+                            ..default()
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    transform: Transform::from_xyz(0.0, 25.0, 1.0),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+        });
+
+    spawn_container(&mut commands, 0.0, ());
+}
+
+pub fn game_over_line_bundle(center_x: f32) -> SpriteBundle {
+    SpriteBundle {
         sprite: Sprite {
             custom_size: Some(Vec2::new(CONTAINER_WIDTH, 3.0)),
             color: GAME_OVER_HINT_COLOR,
             ..default()
         },
-        transform: Transform::from_xyz(0.0, GAME_OVER_HEIGHT + 10.0, 0.0),
+        transform: Transform::from_xyz(center_x, GAME_OVER_HEIGHT + 10.0, 0.0),
         ..default()
-    });
+    }
+}
 
+// only the visuals, the colliders come from the simulation
+pub fn spawn_container(commands: &mut Commands, center_x: f32, tag: impl Bundle + Clone) {
     let container_base = -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET;
-    /* Create the container. */
     commands.spawn((
-        Collider::cuboid(CONTAINER_WIDTH / 2.0, CONTAINER_THICKNESS / 2.0),
+        tag.clone(),
         SpriteBundle {
             sprite: Sprite {
                 custom_size: Some(Vec2::new(CONTAINER_WIDTH, CONTAINER_THICKNESS)),
                 color: CONTAINER_COLOR,
                 ..default()
             },
-            transform: Transform::from_xyz(0.0, container_base, 0.0),
+            transform: Transform::from_xyz(center_x, container_base, 0.0),
             ..default()
         },
     ));
@@ -84,7 +158,7 @@ fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadyS
     let wall_height = CONTAINER_HEIGHT + CONTAINER_THICKNESS;
     let wall_base = container_base + CONTAINER_HEIGHT / 2.0;
     commands.spawn((
-        Collider::cuboid(CONTAINER_THICKNESS / 2.0, wall_height / 2.0),
+        tag.clone(),
         SpriteBundle {
             sprite: Sprite {
                 custom_size: Some(Vec2::new(CONTAINER_THICKNESS, wall_height)),
@@ -92,7 +166,7 @@ fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadyS
                 ..default()
             },
             transform: Transform::from_xyz(
-                (CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
+                center_x + (CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
                 wall_base,
                 0.0,
             ),
@@ -100,7 +174,7 @@ fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadyS
         },
     ));
     commands.spawn((
-        Collider::cuboid(CONTAINER_THICKNESS / 2.0, wall_height / 2.0),
+        tag,
         SpriteBundle {
             sprite: Sprite {
                 custom_size: Some(Vec2::new(CONTAINER_THICKNESS, wall_height)),
@@ -108,7 +182,7 @@ fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadyS
                 ..default()
             },
             transform: Transform::from_xyz(
-                -(CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
+                center_x - (CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
                 wall_base,
                 0.0,
             ),
@@ -117,36 +191,12 @@ fn setup_container(mut commands: Commands, game_already_set_up: Res<GameAlreadyS
     ));
 }
 
-fn setup_app_boundaries(mut commands: Commands, game_already_set_up: Res<GameAlreadySetUp>) {
-    if game_already_set_up.is_set_up {
-        return;
-    }
-
-    commands.spawn((
-        Collider::cuboid(SCREEN_WIDTH / 2.0, 1.0),
-        TransformBundle::from(Transform::from_xyz(0.0, -SCREEN_HEIGHT / 2.0, 0.0)),
-    ));
-
-    commands.spawn((
-        Collider::cuboid(SCREEN_WIDTH / 2.0, 1.0),
-        TransformBundle::from(Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0, 0.0)),
-    ));
-
-    commands.spawn((
-        Collider::cuboid(1.0, SCREEN_HEIGHT / 2.0),
-        TransformBundle::from(Transform::from_xyz(-SCREEN_WIDTH / 2.0, 0.0, 0.0)),
-    ));
-
-    commands.spawn((
-        Collider::cuboid(1.0, SCREEN_HEIGHT / 2.0),
-        TransformBundle::from(Transform::from_xyz(SCREEN_WIDTH / 2.0, 0.0, 0.0)),
-    ));
-}
-
 fn setup_merge_guide(
     mut commands: Commands,
     game_already_set_up: Res<GameAlreadySetUp>,
     asset_server: Res<AssetServer>,
+    catalog: Res<FruitCatalog>,
+    layout: Res<Layout>,
 ) {
     if game_already_set_up.is_set_up {
         return;
@@ -159,23 +209,31 @@ fn setup_merge_guide(
             .min(size / 3.5 + 30.0)
     };
 
-    let mut offset: f32 = 0.0;
-    for (size, file_name, _) in KNOWN_TYPES.into_iter() {
-        print!("{}, ", size * 1.2);
-        let texture_handle = asset_server.load(format!("{}.png", file_name));
-        let pos_x = -SCREEN_WIDTH / 2.0 + 60.0;
-        let pos_y = (-SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET) + offset;
-        let normalized_size = normalize_size(size);
-        offset += normalized_size + 10.0;
-        commands.spawn((SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(1.0, 1.0) * normalized_size),
+    let sizes: Vec<f32> = catalog
+        .fruits
+        .iter()
+        .map(|kind| normalize_size(kind.size))
+        .collect();
+    let (positions, scale) = layout.merge_guide(&sizes);
+    for (index, kind) in catalog.fruits.iter().enumerate() {
+        let texture_handle = asset_server.load(&kind.texture);
+        let normalized_size = sizes[index];
+        commands.spawn((
+            MergeGuideEntry {
+                index,
+                size: normalized_size,
+            },
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(1.0, 1.0) * normalized_size),
+                    ..default()
+                },
+                texture: texture_handle,
+                transform: Transform::from_translation(positions[index].extend(0.0))
+                    .with_scale(Vec3::new(scale, scale, 1.0)),
                 ..default()
             },
-            texture: texture_handle,
-            transform: Transform::from_xyz(pos_x, pos_y, 0.0),
-            ..default()
-        },));
+        ));
     }
 }
 
@@ -183,25 +241,25 @@ fn setup_score(
     mut commands: Commands,
     game_already_set_up: Res<GameAlreadySetUp>,
     score_tracker: Res<ScoreTracker>,
+    layout: Res<Layout>,
 ) {
     if game_already_set_up.is_set_up {
         return;
     }
 
     commands
-        .spawn(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(100.0, 50.0)),
-                color: TRANSPARENT,
+        .spawn((
+            ScorePanel,
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(100.0, 50.0)),
+                    color: TRANSPARENT,
+                    ..default()
+                },
+                transform: Transform::from_translation(layout.score_panel().extend(0.0)),
                 ..default()
             },
-            transform: Transform::from_xyz(
-                -CONTAINER_WIDTH / 2.0 - NEXT_PREVIEW_OFFSET,
-                CONTAINER_HEIGHT / 2.0 + KNOWN_TYPES[5].0 / 2.0 + NEXT_PREVIEW_LABEL_SIZE / 2.0,
-                0.0,
-            ),
-            ..default()
-        })
+        ))
         .with_children(|builder| {
             builder.spawn((
                 Score,
@@ -219,6 +277,22 @@ fn setup_score(
                     ..default()
                 },
             ));
+            builder.spawn((
+                ModeStatus,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: TEXT_COLOR,
+                            ..default()
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    transform: Transform::from_xyz(0.0, -NEXT_PREVIEW_LABEL_SIZE, 1.0),
+                    ..default()
+                },
+            ));
         });
 }
 
@@ -226,16 +300,23 @@ fn setup_preview(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     next_generator: Res<NextGenerator>,
+    catalog: Res<FruitCatalog>,
+    layout: Res<Layout>,
+    existing_preview: Query<(), With<Preview>>,
 ) {
-    let file_name = &next_generator.current_fruit.file_name;
-    let texture_handle = asset_server.load(format!("{}.png", file_name));
+    // still there when resuming from a pause
+    if !existing_preview.is_empty() {
+        return;
+    }
+    let current = catalog.kind(&next_generator.current_fruit);
+    let texture_handle = asset_server.load(&current.texture);
     commands
         .spawn((
             Preview,
             PreviewPart,
             SpriteBundle {
                 sprite: Sprite {
-                    custom_size: Some(Vec2::new(1.0, 1.0) * next_generator.current_fruit.size),
+                    custom_size: Some(Vec2::new(1.0, 1.0) * current.size),
                     ..default()
                 },
                 texture: texture_handle,
@@ -259,47 +340,64 @@ fn setup_preview(
             ));
         });
 
-    let file_name = &next_generator.next_fruit.file_name;
-    let texture_handle = asset_server.load(format!("{}.png", file_name));
-    commands.spawn((
-        NextPreview,
-        PreviewPart,
+    let next = catalog.kind(&next_generator.next_fruit);
+    spawn_preview_panel(
+        &mut commands,
+        (NextPanel, NextPreview),
+        "NEXT",
+        layout.next_panel(),
         SpriteBundle {
             sprite: Sprite {
-                custom_size: Some(Vec2::new(1.0, 1.0) * next_generator.next_fruit.size),
+                custom_size: Some(Vec2::new(1.0, 1.0) * next.size),
                 ..default()
             },
-            texture: texture_handle,
-            transform: Transform::from_xyz(
-                CONTAINER_WIDTH / 2.0 + NEXT_PREVIEW_OFFSET,
-                CONTAINER_HEIGHT / 2.0,
-                0.0,
-            ),
+            texture: asset_server.load(&next.texture),
             ..default()
         },
-    ));
+    );
+
+    // empty until the first hold
+    let held = next_generator.held_fruit.map(|fruit| catalog.kind(&fruit));
+    spawn_preview_panel(
+        &mut commands,
+        (HoldPanel, HoldPreview),
+        "HOLD",
+        layout.hold_panel(),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1.0, 1.0) * held.map_or(0.0, |kind| kind.size)),
+                ..default()
+            },
+            texture: held.map_or_else(Handle::default, |kind| asset_server.load(&kind.texture)),
+            visibility: if held.is_some() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+    );
+}
 
+pub fn spawn_preview_panel<P: Bundle, F: Bundle>(
+    commands: &mut Commands,
+    (panel, fruit_marker): (P, F),
+    label: &str,
+    position: Vec2,
+    fruit: SpriteBundle,
+) {
     commands
         .spawn((
+            panel,
             PreviewPart,
-            SpriteBundle {
-                sprite: Sprite {
-                    custom_size: Some(Vec2::new(10.0, 10.0)),
-                    color: TRANSPARENT,
-                    ..default()
-                },
-                transform: Transform::from_xyz(
-                    CONTAINER_WIDTH / 2.0 + NEXT_PREVIEW_OFFSET,
-                    CONTAINER_HEIGHT / 2.0 + KNOWN_TYPES[5].0 / 2.0 + NEXT_PREVIEW_LABEL_SIZE / 2.0,
-                    0.0,
-                ),
-                ..default()
-            },
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(0.0))),
         ))
         .with_children(|builder| {
+            builder.spawn((fruit_marker, fruit));
+
             builder.spawn(Text2dBundle {
                 text: Text::from_section(
-                    "NEXT",
+                    label,
                     TextStyle {
                         font_size: NEXT_PREVIEW_LABEL_SIZE,
                         color: TEXT_COLOR,
@@ -307,27 +405,24 @@ fn setup_preview(
                     },
                 )
                 .with_alignment(TextAlignment::Center),
-                transform: Transform::from_translation(Vec3::Z),
+                transform: Transform::from_xyz(
+                    0.0,
+                    NEXT_PREVIEW_FRUIT_SIZE / 2.0 + NEXT_PREVIEW_LABEL_SIZE / 2.0,
+                    1.0,
+                ),
                 ..default()
             });
-        });
 
-    commands.spawn((
-        PreviewPart,
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(150.0, 230.0)),
-                color: NEXT_BG_COLOR,
+            builder.spawn(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(PREVIEW_PANEL_WIDTH, PREVIEW_PANEL_HEIGHT)),
+                    color: NEXT_BG_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, NEXT_PREVIEW_LABEL_SIZE / 2.0, -1.0),
                 ..default()
-            },
-            transform: Transform::from_xyz(
-                CONTAINER_WIDTH / 2.0 + NEXT_PREVIEW_OFFSET,
-                CONTAINER_HEIGHT / 2.0 + NEXT_PREVIEW_LABEL_SIZE / 2.0,
-                -1.0,
-            ),
-            ..default()
-        },
-    ));
+            });
+        });
 }
 
 fn cleanup_preview(mut commands: Commands, preview_parts: Query<Entity, With<PreviewPart>>) {
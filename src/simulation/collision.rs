@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::COMBO_WINDOW;
+use crate::resources::MassSetting;
+use crate::{resources::ScoreTracker, Fruit};
+
+use super::{create_fruit_bundle_at, FruitsMerged, Side};
+
+#[derive(Component)]
+pub struct MarkForDelete;
+
+// merging again while it's there continues the chain
+#[derive(Component)]
+pub struct ChainLink {
+    pub chain: u32,
+    timer: Timer,
+}
+
+impl ChainLink {
+    fn new(chain: u32) -> Self {
+        Self {
+            chain,
+            timer: Timer::from_seconds(COMBO_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct MarkForMerge {
+    id: Entity,
+    merge_target: Entity,
+}
+
+pub fn collision(
+    mut collisions: EventReader<CollisionEvent>,
+    fruits: Query<&Fruit>,
+    mut commands: Commands,
+) {
+    for collision in collisions.iter() {
+        if let CollisionEvent::Started(collider_a, collider_b, _) = collision {
+            if let Ok([fruit_a, fruit_b]) = fruits.get_many([*collider_a, *collider_b]) {
+                if fruit_a.tier == fruit_b.tier {
+                    let entity_a = commands.entity(*collider_a).id();
+                    let entity_b = commands.entity(*collider_b).id();
+
+                    commands.entity(*collider_a).insert(MarkForMerge {
+                        id: entity_a,
+                        merge_target: entity_b,
+                    });
+                    commands.entity(*collider_b).insert(MarkForMerge {
+                        id: entity_b,
+                        merge_target: entity_a,
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub fn tick_chain_links(
+    mut commands: Commands,
+    mut links: Query<(Entity, &mut ChainLink)>,
+    fixed_time: Res<FixedTime>,
+) {
+    for (entity, mut link) in links.iter_mut() {
+        if link.timer.tick(fixed_time.period).finished() {
+            commands.entity(entity).remove::<ChainLink>();
+        }
+    }
+}
+
+pub fn remove_used_fruits(
+    fruits_marked_for_delete: Query<Entity, With<MarkForDelete>>,
+    mut commands: Commands,
+) {
+    for fruit in fruits_marked_for_delete.iter() {
+        commands.entity(fruit).despawn();
+    }
+}
+
+// wrote all this extra code to try to handle when a single fruit collides with two fruits of the same size at the same time
+// which would remove all three, and then spawn two fruits of the next size up
+// ideal result should be to remove only two of the three, and then spawn one fruit of the next size up
+#[allow(clippy::type_complexity)]
+pub fn merge_fruits(
+    mut fruits_marked_for_merge: Query<(
+        Entity,
+        &mut MarkForMerge,
+        &Fruit,
+        &Transform,
+        Option<&ChainLink>,
+        Option<&Side>,
+    )>,
+    mut commands: Commands,
+    mut score_tracker: ResMut<ScoreTracker>,
+    mut boards: Query<(&Side, &mut ScoreTracker)>,
+    mass_setting: Res<MassSetting>,
+    mut fruits_merged: EventWriter<FruitsMerged>,
+    catalog: Res<FruitCatalog>,
+) {
+    struct ShouldMerge {
+        entities: (Entity, Entity),
+        source: Fruit,
+        merge_result: Option<Fruit>,
+        translation: ((f32, f32), (f32, f32)),
+        chain: u32,
+        side: Option<Side>,
+    }
+    let mut possible_orphans: Vec<(MarkForMerge, Entity, (f32, f32), u32)> = Vec::new();
+    let mut should_merge_list: Vec<ShouldMerge> = Vec::new();
+    for (entity, marked, fruit, transform, link, side) in fruits_marked_for_merge.iter_mut() {
+        let chain = link.map_or(0, |link| link.chain);
+        if let Some((possible_target, _, translation, target_chain)) = possible_orphans
+            .iter()
+            .find(|(prcssed, _, _, _)| prcssed.id == marked.merge_target)
+        {
+            if possible_target.merge_target == marked.id {
+                should_merge_list.push(ShouldMerge {
+                    entities: (marked.id, possible_target.id),
+                    source: *fruit,
+                    merge_result: fruit.merge(&catalog),
+                    translation: (
+                        (transform.translation.x, transform.translation.y),
+                        *translation,
+                    ),
+                    chain: chain.max(*target_chain) + 1,
+                    side: side.copied(),
+                });
+                continue;
+            }
+        }
+
+        if !possible_orphans
+            .iter()
+            .any(|(prcssed, _, _, _)| prcssed.id == marked.id)
+        {
+            possible_orphans.push((
+                marked.clone(),
+                entity,
+                (transform.translation.x, transform.translation.y),
+                chain,
+            ));
+        }
+    }
+
+    for (_, orphan, _, _) in possible_orphans.iter() {
+        commands.entity(*orphan).remove::<MarkForMerge>();
+    }
+
+    for should_merge in should_merge_list.iter() {
+        let new_x = (should_merge.translation.0 .0 + should_merge.translation.1 .0) / 2.0;
+        let new_y = (should_merge.translation.0 .1 + should_merge.translation.1 .1) / 2.0;
+        // Fruit.merged_size returns None if two largest fruits collide
+        // in this case, both are despawned, and no new fruits created
+        let mut points = 0;
+        let entity = should_merge.merge_result.map(|fruit| {
+            // versus boards keep their own score
+            let tracker = match should_merge.side {
+                Some(side) => boards
+                    .iter_mut()
+                    .find(|(board, _)| **board == side)
+                    .map(|(_, tracker)| tracker.into_inner()),
+                None => Some(&mut *score_tracker),
+            };
+            if let Some(tracker) = tracker {
+                points = tracker.add_merge_score(catalog.kind(&fruit).score, should_merge.chain);
+                tracker.reached_tier(fruit.tier);
+            }
+            let center_x = should_merge.side.map_or(0.0, |side| side.center_x());
+            let mut merged = commands.spawn((
+                create_fruit_bundle_at(center_x, new_x - center_x, new_y, fruit, &catalog),
+                AdditionalMassProperties::Mass(mass_setting.get_mass()),
+                ChainLink::new(should_merge.chain),
+            ));
+            if let Some(side) = should_merge.side {
+                merged.insert(side);
+            }
+            merged.id()
+        });
+        let (from_a, from_b) = should_merge.translation;
+        fruits_merged.send(FruitsMerged {
+            position: Vec2::new(new_x, new_y),
+            from: [Vec2::new(from_a.0, from_a.1), Vec2::new(from_b.0, from_b.1)],
+            source: should_merge.source,
+            result: should_merge.merge_result,
+            entity,
+            chain: should_merge.chain,
+            points,
+            side: should_merge.side,
+        });
+
+        commands
+            .entity(should_merge.entities.0)
+            .remove::<(RigidBody, Collider)>()
+            .insert(MarkForDelete);
+        commands
+            .entity(should_merge.entities.1)
+            .remove::<(RigidBody, Collider)>()
+            .insert(MarkForDelete);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::AppState;
+use bevy::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{CONTAINER_THICKNESS, CONTAINER_WIDTH, GAME_OVER_HEIGHT};
+use crate::resources::{DangerCountdown, GameMode, GameOverGrace, RunClock, ScoreTracker};
+
+use super::{Alive, GameOver};
+
+// a fruit has to stay above the line for a while, one out of the container ends the run right away.
+// modes can end it too (time attack, target) or never let the pile end it (zen)
+#[allow(clippy::too_many_arguments)]
+pub fn check_game_over(
+    positions: Query<&Transform, With<Alive>>,
+    fixed_time: Res<FixedTime>,
+    grace: Res<GameOverGrace>,
+    score_tracker: Res<ScoreTracker>,
+    run_clock: Res<RunClock>,
+    catalog: Res<FruitCatalog>,
+    mut countdown: ResMut<DangerCountdown>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut game_over: EventWriter<GameOver>,
+) {
+    if mode_finished(&score_tracker, &run_clock, &catalog) {
+        next_state.set(AppState::GameOverMenu);
+        game_over.send(GameOver);
+        return;
+    }
+    if !score_tracker.mode.has_game_over() {
+        return;
+    }
+
+    let is_over = pile_over(
+        positions.iter(),
+        0.0,
+        &mut countdown,
+        &grace,
+        fixed_time.period.as_secs_f32(),
+    );
+    if is_over {
+        next_state.set(AppState::GameOverMenu);
+        game_over.send(GameOver);
+    }
+}
+
+pub fn pile_over<'a>(
+    positions: impl Iterator<Item = &'a Transform>,
+    center_x: f32,
+    countdown: &mut DangerCountdown,
+    grace: &GameOverGrace,
+    delta_seconds: f32,
+) -> bool {
+    let mut is_over = false;
+    let mut in_danger = false;
+    for position in positions {
+        let x = position.translation.x - center_x;
+        if position.translation.y > GAME_OVER_HEIGHT {
+            in_danger = true;
+        }
+        let edge = CONTAINER_WIDTH / 2.0 + CONTAINER_THICKNESS;
+        if !(-edge..=edge).contains(&x) {
+            is_over = true;
+        }
+    }
+
+    if in_danger {
+        let elapsed = countdown.elapsed.unwrap_or(0.0) + delta_seconds;
+        countdown.elapsed = Some(elapsed);
+        if elapsed >= grace.seconds {
+            is_over = true;
+        }
+    } else if countdown.elapsed.is_some() {
+        // the pile settled back down
+        countdown.elapsed = None;
+    }
+    is_over
+}
+
+// regardless of the pile
+pub fn mode_finished(
+    score_tracker: &ScoreTracker,
+    run_clock: &RunClock,
+    catalog: &FruitCatalog,
+) -> bool {
+    match score_tracker.mode {
+        GameMode::TimeAttack => score_tracker
+            .mode
+            .time_limit()
+            .is_some_and(|limit| run_clock.elapsed >= limit),
+        GameMode::Target => score_tracker.largest_tier + 1 >= catalog.fruits.len(),
+        GameMode::Classic | GameMode::Zen | GameMode::Daily => false,
+    }
+}
@@ -0,0 +1,552 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_rapier2d::prelude::*;
+use rand::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{
+    CONTAINER_BASE_OFFSET, CONTAINER_HEIGHT, CONTAINER_THICKNESS, CONTAINER_WIDTH, GRAVITY, MASS,
+    RESTITUATION, SCREEN_HEIGHT, SCREEN_WIDTH, SIMULATION_STEP, SPAWN_HEIGHT,
+};
+use crate::resources::{
+    DangerCountdown, GameMode, GameOverGrace, GameRng, MassSetting, NextGenerator, NextSeed,
+    RunClock, ScoreTracker, ScoringRules, SpawnTime,
+};
+use crate::{AppState, Fruit};
+
+mod collision;
+use collision::{collision, merge_fruits, remove_used_fruits, tick_chain_links};
+pub use collision::{ChainLink, MarkForDelete, MarkForMerge};
+
+mod snapshot;
+pub use snapshot::{capture_board, BoardSnapshot, FruitSnapshot, PendingRestore};
+use snapshot::{discard_unfit_restore, restore_board};
+
+mod settle;
+pub use settle::{at_rest, settle, settle_until, SettleConfig, SettleOutcome};
+
+mod versus;
+use versus::{
+    check_versus_over, drop_garbage, end_versus, freeze_versus_fruits, restart_versus,
+    send_garbage, setup_versus, versus_drop,
+};
+pub use versus::{
+    versus_playing, Garbage, Side, VersusBoard, VersusDrop, VersusMatch, VersusOutcome, VersusOver,
+};
+
+mod game_over;
+use game_over::check_game_over;
+pub use game_over::mode_finished;
+
+mod physics_manipulations;
+pub use physics_manipulations::MassAltered;
+use physics_manipulations::{
+    change_manipulated_mass_on_slide, clamp_upward_velocity, manipulate_mass, mark_fruits_as_alive,
+};
+
+// the game rules without anything visual, so it also runs without a window
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>()
+            // one physics step per fixed step, so a run plays out the same however fast frames come
+            .insert_resource(FixedTime::new_from_secs(SIMULATION_STEP))
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: SIMULATION_STEP,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0)
+                    .with_default_system_setup(false),
+            )
+            .init_resource::<FruitCatalog>()
+            .init_resource::<SpawnTime>()
+            .init_resource::<GameRng>()
+            .init_resource::<NextSeed>()
+            .init_resource::<NextGenerator>()
+            .init_resource::<ScoreTracker>()
+            .init_resource::<ScoringRules>()
+            .init_resource::<GameMode>()
+            .init_resource::<MassSetting>()
+            .init_resource::<RunClock>()
+            .init_resource::<GameOverGrace>()
+            .init_resource::<DangerCountdown>()
+            // kept until a fixed step has read them, some frames have none
+            .init_resource::<Events<RunStarted>>()
+            .init_resource::<Events<DropFruit>>()
+            .init_resource::<Events<HoldFruit>>()
+            .init_resource::<Events<RestartGame>>()
+            .init_resource::<Events<VersusDrop>>()
+            .add_event::<FruitHeld>()
+            .add_event::<FruitDropped>()
+            .add_event::<FruitsMerged>()
+            .add_event::<GameOver>()
+            .add_event::<VersusOver>()
+            .add_systems(Startup, (setup_container_walls, setup_app_boundaries))
+            .add_systems(
+                FixedUpdate,
+                (
+                    (clear_board, begin_run)
+                        .before(tick_run_clock)
+                        .run_if(on_event::<RestartGame>()),
+                    tick_run_clock.before(hold_fruit),
+                    hold_fruit.before(drop_fruit),
+                    drop_fruit,
+                    check_game_over,
+                )
+                    .in_set(SimulationSet)
+                    .run_if(simulation_running),
+            )
+            // fruits fall and merge the same way in a versus match
+            .add_systems(
+                FixedUpdate,
+                (
+                    collision,
+                    clamp_upward_velocity,
+                    mark_fruits_as_alive.before(merge_fruits),
+                    manipulate_mass.before(merge_fruits),
+                    change_manipulated_mass_on_slide.before(merge_fruits),
+                    tick_chain_links.before(merge_fruits),
+                    merge_fruits.after(collision),
+                    remove_used_fruits.after(merge_fruits),
+                )
+                    .in_set(SimulationSet)
+                    .run_if(board_running),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    versus_drop,
+                    send_garbage.after(merge_fruits),
+                    drop_garbage,
+                    check_versus_over,
+                )
+                    .in_set(SimulationSet)
+                    .run_if(in_state(AppState::Versus).and_then(versus_playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    restart_versus.run_if(on_event::<RestartGame>()),
+                    freeze_versus_fruits
+                        .after(check_versus_over)
+                        .run_if(on_event::<VersusOver>()),
+                )
+                    .in_set(SimulationSet)
+                    .run_if(in_state(AppState::Versus)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    Events::<RunStarted>::update_system,
+                    Events::<DropFruit>::update_system,
+                    Events::<HoldFruit>::update_system,
+                    Events::<RestartGame>::update_system,
+                    Events::<VersusDrop>::update_system,
+                )
+                    .after(SimulationSet),
+            )
+            // rapier steps right after the rules, on the same fixed step
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                )
+                    .chain()
+                    .after(SimulationSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_deferred
+                        .after(SimulationSet)
+                        .before(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                        .in_set(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                        .in_set(PhysicsSet::Writeback),
+                ),
+            )
+            // fruits despawned between fixed steps have to leave the physics world too
+            .configure_sets(
+                PostUpdate,
+                (PhysicsSet::SyncBackend, PhysicsSet::SyncBackendFlush)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                        .in_set(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                ),
+            )
+            .add_systems(
+                OnExit(AppState::StartMenu),
+                (
+                    discard_unfit_restore,
+                    apply_deferred,
+                    begin_run.run_if(
+                        not(resource_exists::<PendingRestore>())
+                            .and_then(not(resource_exists::<VersusMatch>())),
+                    ),
+                    restore_board.run_if(resource_exists::<PendingRestore>()),
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(AppState::GameOverMenu), freeze_fruits)
+            .add_systems(OnEnter(AppState::Paused), pause_physics)
+            .add_systems(OnExit(AppState::Paused), resume_physics)
+            .add_systems(
+                OnExit(AppState::GameOverMenu),
+                (
+                    clear_board,
+                    begin_run.run_if(not(resource_exists::<VersusMatch>())),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Versus), setup_versus)
+            .add_systems(OnExit(AppState::Versus), end_versus);
+    }
+}
+
+// runs in FixedUpdate, input sent in Update is seen on the next step
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationSet;
+
+// nothing happens until the state is set to InGame and DropFruit events are sent,
+// NextSeed makes the run reproducible
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        HierarchyPlugin,
+        SimulationPlugin,
+    ));
+    app
+}
+
+#[derive(Event)]
+pub struct RunStarted {
+    pub seed: u64,
+}
+
+// ignored while SpawnTime is running
+#[derive(Event)]
+pub struct DropFruit {
+    pub x: f32,
+}
+
+// ignored if the hold was used since the last drop
+#[derive(Event)]
+pub struct HoldFruit;
+
+#[derive(Event)]
+pub struct FruitHeld {
+    pub fruit: Fruit,
+}
+
+#[derive(Event)]
+pub struct FruitDropped {
+    pub entity: Entity,
+    pub fruit: Fruit,
+    // requested position, before clamping and jitter
+    pub x: f32,
+}
+
+#[derive(Event)]
+pub struct FruitsMerged {
+    pub position: Vec2,
+    // where the two merged fruits were, and which tier they were
+    pub from: [Vec2; 2],
+    pub source: Fruit,
+    // None when two of the largest fruits merge
+    pub result: Option<Fruit>,
+    pub entity: Option<Entity>,
+    // how far into a chain of merges this one is, 1 if it started none
+    pub chain: u32,
+    pub points: u32,
+    pub side: Option<Side>,
+}
+
+// the state switches to GameOverMenu on the next frame
+#[derive(Event)]
+pub struct GameOver;
+
+#[derive(Event)]
+pub struct RestartGame;
+
+// false as soon as the run ended, before the state switches
+pub fn simulation_running(
+    state: Res<State<AppState>>,
+    next_state: Res<NextState<AppState>>,
+) -> bool {
+    matches!(state.get(), AppState::InGame | AppState::Replay) && next_state.0.is_none()
+}
+
+// includes a versus match
+pub fn board_running(state: Res<State<AppState>>, next_state: Res<NextState<AppState>>) -> bool {
+    matches!(
+        state.get(),
+        AppState::InGame | AppState::Replay | AppState::Versus
+    ) && next_state.0.is_none()
+}
+
+// tracks fruits that should be considered for game over conditions
+#[derive(Component)]
+pub struct Alive;
+
+#[derive(Component)]
+pub struct TimeSinceSpawn {
+    pub timer: Timer,
+}
+
+pub fn create_fruit_bundle(
+    pos_x: f32,
+    pos_y: f32,
+    fruit: Fruit,
+    catalog: &FruitCatalog,
+) -> (
+    Fruit,
+    TimeSinceSpawn,
+    RigidBody,
+    TransformBundle,
+    Collider,
+    GravityScale,
+    Restitution,
+    ActiveEvents,
+    Velocity,
+) {
+    create_fruit_bundle_at(0.0, pos_x, pos_y, fruit, catalog)
+}
+
+// pos_x is relative to center_x
+pub fn create_fruit_bundle_at(
+    center_x: f32,
+    pos_x: f32,
+    pos_y: f32,
+    fruit: Fruit,
+    catalog: &FruitCatalog,
+) -> (
+    Fruit,
+    TimeSinceSpawn,
+    RigidBody,
+    TransformBundle,
+    Collider,
+    GravityScale,
+    Restitution,
+    ActiveEvents,
+    Velocity,
+) {
+    // make sure spawning position is in bounds
+    // adding one pixel on either edge to prevent collision against wall on drop
+    let kind = catalog.kind(&fruit);
+    let size = kind.size;
+    let pos_x_in_bounds = pos_x_in_bounds(pos_x, size);
+    (
+        fruit,
+        TimeSinceSpawn {
+            timer: Timer::from_seconds(1.0, TimerMode::Once),
+        },
+        RigidBody::Dynamic,
+        TransformBundle::from(Transform::from_xyz(center_x + pos_x_in_bounds, pos_y, 0.0)),
+        kind.collider.collider(size),
+        GravityScale(GRAVITY),
+        Restitution::coefficient(RESTITUATION),
+        ActiveEvents::COLLISION_EVENTS,
+        Velocity {
+            linvel: Vec2::new(0.0, 0.0),
+            angvel: 0.0,
+        },
+    )
+}
+
+pub fn pos_x_in_bounds(raw_x: f32, sprite_size: f32) -> f32 {
+    match raw_x {
+        x if x < 0.0 => x.max((-CONTAINER_WIDTH / 2.0 + sprite_size / 2.0) + 1.0),
+        x if x > 0.0 => x.min((CONTAINER_WIDTH / 2.0 - sprite_size / 2.0) - 1.0),
+        _ => raw_x,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drop_fruit(
+    mut commands: Commands,
+    mut drop_requests: EventReader<DropFruit>,
+    mut fruit_dropped: EventWriter<FruitDropped>,
+    mut click_buffer: ResMut<SpawnTime>,
+    fixed_time: Res<FixedTime>,
+    mut next_generator: ResMut<NextGenerator>,
+    mut game_rng: ResMut<GameRng>,
+    mut score_tracker: ResMut<ScoreTracker>,
+    catalog: Res<FruitCatalog>,
+) {
+    click_buffer.timer.tick(fixed_time.period);
+    for drop_request in drop_requests.iter() {
+        if !click_buffer.timer.finished() {
+            continue;
+        }
+        click_buffer.start_new_timer(score_tracker.mode);
+        let next_fruit = next_generator.current_fruit;
+        score_tracker.reached_tier(next_fruit.tier);
+        score_tracker.drops += 1;
+        next_generator.next(&mut game_rng, &catalog); // after spawning current, go to next
+        let pos_x = drop_request.x + game_rng.rng().gen_range(-1.0..1.0);
+        let entity = commands
+            .spawn((
+                create_fruit_bundle(pos_x, SPAWN_HEIGHT, next_fruit, &catalog),
+                AdditionalMassProperties::Mass(MASS),
+            ))
+            .id();
+        fruit_dropped.send(FruitDropped {
+            entity,
+            fruit: next_fruit,
+            x: drop_request.x,
+        });
+    }
+}
+
+fn hold_fruit(
+    mut hold_requests: EventReader<HoldFruit>,
+    mut fruit_held: EventWriter<FruitHeld>,
+    mut next_generator: ResMut<NextGenerator>,
+    mut game_rng: ResMut<GameRng>,
+    catalog: Res<FruitCatalog>,
+) {
+    for _ in hold_requests.iter() {
+        let fruit = next_generator.current_fruit;
+        if next_generator.hold(&mut game_rng, &catalog) {
+            fruit_held.send(FruitHeld { fruit });
+        }
+    }
+}
+
+fn tick_run_clock(mut run_clock: ResMut<RunClock>, fixed_time: Res<FixedTime>) {
+    run_clock.tick(fixed_time.period.as_secs_f32());
+}
+
+fn freeze_fruits(mut fruits: Query<&mut RigidBody, With<Fruit>>) {
+    for mut fruit in fruits.iter_mut() {
+        *fruit = RigidBody::Fixed;
+    }
+}
+
+// rapier doesn't go by simulation_running
+fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
+fn clear_board(
+    mut commands: Commands,
+    mut score_tracker: ResMut<ScoreTracker>,
+    fruits: Query<Entity, With<Fruit>>,
+) {
+    score_tracker.reset();
+    for fruit in fruits.iter() {
+        commands.entity(fruit).despawn_recursive();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn begin_run(
+    mut next_seed: ResMut<NextSeed>,
+    mut game_rng: ResMut<GameRng>,
+    mut next_generator: ResMut<NextGenerator>,
+    mut click_buffer: ResMut<SpawnTime>,
+    mut run_clock: ResMut<RunClock>,
+    mut danger_countdown: ResMut<DangerCountdown>,
+    mut score_tracker: ResMut<ScoreTracker>,
+    mut run_started: EventWriter<RunStarted>,
+    scoring_rules: Res<ScoringRules>,
+    game_mode: Res<GameMode>,
+    catalog: Res<FruitCatalog>,
+) {
+    let seed = next_seed
+        .seed
+        .take()
+        .unwrap_or_else(|| game_mode.fresh_seed());
+    game_rng.reseed(seed);
+    *next_generator = NextGenerator::new(&mut game_rng, &catalog);
+    *click_buffer = SpawnTime::default();
+    *run_clock = RunClock::default();
+    *danger_countdown = DangerCountdown::default();
+    score_tracker.rules = *scoring_rules;
+    score_tracker.mode = *game_mode;
+    score_tracker.recorded = false;
+    run_started.send(RunStarted {
+        seed: game_rng.seed,
+    });
+}
+
+fn setup_container_walls(mut commands: Commands) {
+    spawn_container_walls(&mut commands, 0.0, ());
+}
+
+pub fn spawn_container_walls(commands: &mut Commands, center_x: f32, tag: impl Bundle + Clone) {
+    let container_base = -SCREEN_HEIGHT / 2.0 + CONTAINER_BASE_OFFSET;
+    commands.spawn((
+        tag.clone(),
+        Collider::cuboid(CONTAINER_WIDTH / 2.0, CONTAINER_THICKNESS / 2.0),
+        TransformBundle::from(Transform::from_xyz(center_x, container_base, 0.0)),
+    ));
+
+    let wall_height = CONTAINER_HEIGHT + CONTAINER_THICKNESS;
+    let wall_base = container_base + CONTAINER_HEIGHT / 2.0;
+    commands.spawn((
+        tag.clone(),
+        Collider::cuboid(CONTAINER_THICKNESS / 2.0, wall_height / 2.0),
+        TransformBundle::from(Transform::from_xyz(
+            center_x + (CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
+            wall_base,
+            0.0,
+        )),
+    ));
+    commands.spawn((
+        tag,
+        Collider::cuboid(CONTAINER_THICKNESS / 2.0, wall_height / 2.0),
+        TransformBundle::from(Transform::from_xyz(
+            center_x - (CONTAINER_WIDTH + CONTAINER_THICKNESS) / 2.0,
+            wall_base,
+            0.0,
+        )),
+    ));
+}
+
+fn setup_app_boundaries(mut commands: Commands) {
+    commands.spawn((
+        Collider::cuboid(SCREEN_WIDTH / 2.0, 1.0),
+        TransformBundle::from(Transform::from_xyz(0.0, -SCREEN_HEIGHT / 2.0, 0.0)),
+    ));
+
+    commands.spawn((
+        Collider::cuboid(SCREEN_WIDTH / 2.0, 1.0),
+        TransformBundle::from(Transform::from_xyz(0.0, SCREEN_HEIGHT / 2.0, 0.0)),
+    ));
+
+    commands.spawn((
+        Collider::cuboid(1.0, SCREEN_HEIGHT / 2.0),
+        TransformBundle::from(Transform::from_xyz(-SCREEN_WIDTH / 2.0, 0.0, 0.0)),
+    ));
+
+    commands.spawn((
+        Collider::cuboid(1.0, SCREEN_HEIGHT / 2.0),
+        TransformBundle::from(Transform::from_xyz(SCREEN_WIDTH / 2.0, 0.0, 0.0)),
+    ));
+}
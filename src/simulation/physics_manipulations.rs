@@ -23,13 +23,13 @@ pub fn clamp_upward_velocity(mut velocities: Query<&mut Velocity>) {
 #[allow(clippy::type_complexity)]
 pub fn mark_fruits_as_alive(
     mut unalive_fruits: Query<(Entity, &mut TimeSinceSpawn), (With<Fruit>, Without<Alive>)>,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut commands: Commands,
 ) {
     for (entity, mut time_since_spawn) in unalive_fruits.iter_mut() {
-        time_since_spawn.timer.tick(time.delta());
+        time_since_spawn.timer.tick(fixed_time.period);
         if !time_since_spawn.timer.finished() {
-            return;
+            continue;
         }
 
         commands.entity(entity).insert(Alive);
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::Instant;
+use bevy_rapier2d::prelude::*;
+
+use crate::constants::SIMULATION_STEP;
+use crate::{AppState, Fruit};
+
+use super::{MarkForDelete, MarkForMerge};
+
+pub struct SettleConfig {
+    // game time per step, for the timers and the physics alike
+    pub step: f32,
+    // in pixels per second and radians per second
+    pub rest_speed: f32,
+    pub rest_angular_speed: f32,
+    pub max_steps: u32,
+}
+
+impl Default for SettleConfig {
+    fn default() -> Self {
+        Self {
+            // the board real-time play would reach
+            step: SIMULATION_STEP,
+            rest_speed: 5.0,
+            rest_angular_speed: 0.1,
+            // a minute of play
+            max_steps: 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettleOutcome {
+    AtRest { steps: u32 },
+    // the run ended (or was paused) on the way
+    Stopped { steps: u32 },
+    StepCap,
+}
+
+// no merge waiting either
+pub fn at_rest(world: &mut World, config: &SettleConfig) -> bool {
+    let mut pending = world.query_filtered::<(), Or<(With<MarkForMerge>, With<MarkForDelete>)>>();
+    if pending.iter(world).next().is_some() {
+        return false;
+    }
+    let mut velocities = world.query_filtered::<&Velocity, With<Fruit>>();
+    velocities.iter(world).all(|velocity| {
+        velocity.linvel.length() < config.rest_speed
+            && velocity.angvel.abs() < config.rest_angular_speed
+    })
+}
+
+pub fn settle(app: &mut App, config: &SettleConfig) -> SettleOutcome {
+    settle_until(app, config, |world| at_rest(world, config))
+}
+
+// at least one step, until done holds, the run stops or max_steps is reached
+pub fn settle_until(
+    app: &mut App,
+    config: &SettleConfig,
+    mut done: impl FnMut(&mut World) -> bool,
+) -> SettleOutcome {
+    let world = &mut app.world;
+    let time_update = world
+        .remove_resource::<TimeUpdateStrategy>()
+        .unwrap_or_default();
+    world.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        config.step,
+    )));
+    let period = std::mem::replace(
+        &mut world.resource_mut::<FixedTime>().period,
+        Duration::from_secs_f32(config.step),
+    );
+    let timestep_mode = world.resource::<RapierConfiguration>().timestep_mode;
+    world.resource_mut::<RapierConfiguration>().timestep_mode = TimestepMode::Fixed {
+        dt: config.step,
+        substeps: 1,
+    };
+
+    let mut outcome = SettleOutcome::StepCap;
+    for steps in 1..=config.max_steps {
+        app.update();
+        let state = app.world.resource::<State<AppState>>().get();
+        if !matches!(state, AppState::InGame | AppState::Replay) {
+            outcome = SettleOutcome::Stopped { steps };
+            break;
+        }
+        if done(&mut app.world) {
+            outcome = SettleOutcome::AtRest { steps };
+            break;
+        }
+    }
+
+    let world = &mut app.world;
+    world.resource_mut::<RapierConfiguration>().timestep_mode = timestep_mode;
+    world.resource_mut::<FixedTime>().period = period;
+    if let TimeUpdateStrategy::Automatic = time_update {
+        // the next real frame shouldn't make up for the steps
+        world
+            .resource_mut::<Time>()
+            .update_with_instant(Instant::now());
+    }
+    world.insert_resource(time_update);
+    outcome
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::FruitCatalog;
+use crate::resources::{
+    DangerCountdown, GameMode, GameRng, NextGenerator, RunClock, ScoreTracker, ScoringRules,
+    SpawnTime,
+};
+use crate::Fruit;
+
+use super::{create_fruit_bundle, Alive, MarkForDelete, MassAltered, TimeSinceSpawn};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BoardSnapshot {
+    pub fruits: Vec<FruitSnapshot>,
+    pub score: u32,
+    pub largest_tier: usize,
+    pub current_fruit: usize,
+    pub next_fruit: usize,
+    pub seed: u64,
+    pub rng_word_pos: u64,
+    pub spawn_cooldown: f32,
+    pub spawn_cooldown_elapsed: f32,
+    pub run_time: f32,
+    pub run_frame: u32,
+    pub danger_elapsed: Option<f32>,
+    pub rules: ScoringRules,
+    pub best_combo: u32,
+    pub combo_merges: u32,
+    pub held_fruit: Option<usize>,
+    pub hold_used: bool,
+    pub mode: GameMode,
+    pub drops: u32,
+    pub autoplayed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FruitSnapshot {
+    pub tier: usize,
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub linvel: [f32; 2],
+    pub angvel: f32,
+    // None once the fruit is Alive
+    pub spawn_elapsed: Option<f32>,
+    pub mass_altered: bool,
+    pub mass: f32,
+}
+
+impl BoardSnapshot {
+    // a save from another roster may have tiers this catalog doesn't
+    pub fn fits(&self, catalog: &FruitCatalog) -> bool {
+        let tiers = catalog.fruits.len();
+        self.fruits.iter().all(|fruit| fruit.tier < tiers)
+            && self.current_fruit < tiers
+            && self.next_fruit < tiers
+            && self.held_fruit.is_none_or(|tier| tier < tiers)
+            && self.largest_tier < tiers
+    }
+}
+
+// put back instead of starting a fresh run the next time one begins
+#[derive(Resource)]
+pub struct PendingRestore(pub BoardSnapshot);
+
+pub fn discard_unfit_restore(
+    mut commands: Commands,
+    pending: Option<Res<PendingRestore>>,
+    catalog: Res<FruitCatalog>,
+) {
+    if let Some(pending) = pending {
+        if !pending.0.fits(&catalog) {
+            warn!("the board to restore has fruits the catalog doesn't, starting a new run");
+            commands.remove_resource::<PendingRestore>();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn capture_board(world: &mut World) -> BoardSnapshot {
+    let mut fruits = world.query_filtered::<(
+        &Fruit,
+        &Transform,
+        &Velocity,
+        &AdditionalMassProperties,
+        Option<&TimeSinceSpawn>,
+        Option<&MassAltered>,
+    ), Without<MarkForDelete>>();
+    let fruits = fruits
+        .iter(world)
+        .map(
+            |(fruit, transform, velocity, mass_properties, time_since_spawn, mass_altered)| {
+                FruitSnapshot {
+                    tier: fruit.tier,
+                    position: [transform.translation.x, transform.translation.y],
+                    rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+                    linvel: velocity.linvel.to_array(),
+                    angvel: velocity.angvel,
+                    spawn_elapsed: time_since_spawn.map(|spawned| spawned.timer.elapsed_secs()),
+                    mass_altered: mass_altered.is_some(),
+                    mass: match mass_properties {
+                        AdditionalMassProperties::Mass(mass) => *mass,
+                        AdditionalMassProperties::MassProperties(props) => props.mass,
+                    },
+                }
+            },
+        )
+        .collect();
+
+    let score_tracker = world.resource::<ScoreTracker>();
+    let next_generator = world.resource::<NextGenerator>();
+    let game_rng = world.resource::<GameRng>();
+    let spawn_time = world.resource::<SpawnTime>();
+    let run_clock = world.resource::<RunClock>();
+    BoardSnapshot {
+        fruits,
+        score: score_tracker.score,
+        largest_tier: score_tracker.largest_tier,
+        current_fruit: next_generator.current_fruit.tier,
+        next_fruit: next_generator.next_fruit.tier,
+        seed: game_rng.seed,
+        rng_word_pos: game_rng.word_pos(),
+        spawn_cooldown: spawn_time.timer.duration().as_secs_f32(),
+        spawn_cooldown_elapsed: spawn_time.timer.elapsed_secs(),
+        run_time: run_clock.elapsed,
+        run_frame: run_clock.frame,
+        danger_elapsed: world.resource::<DangerCountdown>().elapsed,
+        rules: score_tracker.rules,
+        best_combo: score_tracker.best_combo,
+        combo_merges: score_tracker.combo_merges,
+        held_fruit: next_generator.held_fruit.map(|fruit| fruit.tier),
+        hold_used: next_generator.hold_used,
+        mode: score_tracker.mode,
+        drops: score_tracker.drops,
+        autoplayed: score_tracker.autoplayed,
+    }
+}
+
+pub fn restore_board(world: &mut World) {
+    let Some(PendingRestore(snapshot)) = world.remove_resource::<PendingRestore>() else {
+        return;
+    };
+
+    let mut existing = world.query_filtered::<Entity, With<Fruit>>();
+    let existing: Vec<Entity> = existing.iter(world).collect();
+    for entity in existing {
+        despawn_with_children_recursive(world, entity);
+    }
+
+    world.resource_scope(|world, catalog: Mut<FruitCatalog>| {
+        for fruit in snapshot.fruits.iter() {
+            let [x, y] = fruit.position;
+            let mut entity = world.spawn((
+                create_fruit_bundle(x, y, Fruit { tier: fruit.tier }, &catalog),
+                AdditionalMassProperties::Mass(fruit.mass),
+            ));
+            entity.insert((
+                Transform::from_xyz(x, y, 0.0).with_rotation(Quat::from_rotation_z(fruit.rotation)),
+                Velocity {
+                    linvel: Vec2::from_array(fruit.linvel),
+                    angvel: fruit.angvel,
+                },
+            ));
+            match fruit.spawn_elapsed {
+                Some(elapsed) => {
+                    let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
+                    timer.set_elapsed(Duration::from_secs_f32(elapsed));
+                    entity.insert(TimeSinceSpawn { timer });
+                }
+                None => {
+                    entity.remove::<TimeSinceSpawn>().insert(Alive);
+                }
+            }
+            if fruit.mass_altered {
+                entity.insert(MassAltered);
+            }
+        }
+    });
+
+    let mut score_tracker = world.resource_mut::<ScoreTracker>();
+    score_tracker.score = snapshot.score;
+    score_tracker.largest_tier = snapshot.largest_tier;
+    // chains in progress are not kept, the next merge starts a new one
+    score_tracker.rules = snapshot.rules;
+    score_tracker.best_combo = snapshot.best_combo;
+    score_tracker.combo_merges = snapshot.combo_merges;
+    score_tracker.mode = snapshot.mode;
+    score_tracker.drops = snapshot.drops;
+    score_tracker.autoplayed = snapshot.autoplayed;
+
+    world.insert_resource(NextGenerator {
+        current_fruit: Fruit {
+            tier: snapshot.current_fruit,
+        },
+        next_fruit: Fruit {
+            tier: snapshot.next_fruit,
+        },
+        held_fruit: snapshot.held_fruit.map(|tier| Fruit { tier }),
+        hold_used: snapshot.hold_used,
+        should_update_previews: true,
+    });
+    world.insert_resource(GameRng::restore(snapshot.seed, snapshot.rng_word_pos));
+
+    let mut timer = Timer::from_seconds(snapshot.spawn_cooldown, TimerMode::Once);
+    timer.set_elapsed(Duration::from_secs_f32(snapshot.spawn_cooldown_elapsed));
+    world.insert_resource(SpawnTime { timer });
+    world.insert_resource(RunClock {
+        elapsed: snapshot.run_time,
+        frame: snapshot.run_frame,
+    });
+    world.insert_resource(DangerCountdown {
+        elapsed: snapshot.danger_elapsed,
+    });
+}
@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::prelude::*;
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{
+    CONTAINER_WIDTH, GARBAGE_BATCH, GARBAGE_DELAY, GARBAGE_MIN_TIER, MASS, SPAWN_HEIGHT,
+    VERSUS_ARENA_X, VERSUS_BOARD_OFFSET,
+};
+use crate::resources::{
+    random_seed, Aim, DangerCountdown, GameMode, GameOverGrace, GameRng, NextGenerator,
+    ScoreTracker, SpawnTime,
+};
+use crate::Fruit;
+
+use super::game_over::pile_over;
+use super::{create_fruit_bundle_at, spawn_container_walls, Alive, FruitDropped, FruitsMerged};
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub const BOTH: [Side; 2] = [Side::Left, Side::Right];
+
+    pub fn center_x(&self) -> f32 {
+        match self {
+            Side::Left => VERSUS_ARENA_X - VERSUS_BOARD_OFFSET,
+            Side::Right => VERSUS_ARENA_X + VERSUS_BOARD_OFFSET,
+        }
+    }
+
+    pub fn opponent(&self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Side::Left => "left",
+            Side::Right => "right",
+        }
+    }
+}
+
+// insert before switching to AppState::Versus
+#[derive(Resource)]
+pub struct VersusMatch {
+    pub seed: u64,
+    pub outcome: Option<VersusOutcome>,
+}
+
+impl VersusMatch {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            seed: seed.unwrap_or_else(random_seed),
+            outcome: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VersusOutcome {
+    Won(Side),
+    // both piles ended the run on the same frame
+    Draw,
+}
+
+// x is relative to the center of the side's container
+#[derive(Event)]
+pub struct VersusDrop {
+    pub side: Side,
+    pub x: f32,
+}
+
+#[derive(Event)]
+pub struct VersusOver {
+    pub outcome: VersusOutcome,
+}
+
+#[derive(Component)]
+pub struct Garbage {
+    pub pending: u32,
+    timer: Timer,
+}
+
+impl Default for Garbage {
+    fn default() -> Self {
+        Self {
+            pending: 0,
+            timer: Timer::from_seconds(GARBAGE_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+impl Garbage {
+    fn receive(&mut self, count: u32) {
+        self.pending += count;
+        self.timer.reset();
+    }
+}
+
+// the regular run has the same as resources
+#[derive(Bundle)]
+pub struct VersusBoard {
+    side: Side,
+    next_generator: NextGenerator,
+    score_tracker: ScoreTracker,
+    spawn_time: SpawnTime,
+    game_rng: GameRng,
+    danger_countdown: DangerCountdown,
+    aim: Aim,
+    garbage: Garbage,
+}
+
+impl VersusBoard {
+    // both sides share the seed, so they get the same fruits in the same order
+    pub fn new(side: Side, seed: u64, catalog: &FruitCatalog) -> Self {
+        let mut game_rng = GameRng::from_seed(seed);
+        Self {
+            side,
+            next_generator: NextGenerator::new(&mut game_rng, catalog),
+            score_tracker: ScoreTracker::default(),
+            spawn_time: SpawnTime::default(),
+            game_rng,
+            danger_countdown: DangerCountdown::default(),
+            aim: Aim::default(),
+            garbage: Garbage::default(),
+        }
+    }
+}
+
+pub fn versus_playing(versus: Option<Res<VersusMatch>>) -> bool {
+    versus.is_some_and(|versus| versus.outcome.is_none())
+}
+
+pub fn setup_versus(mut commands: Commands, versus: Res<VersusMatch>, catalog: Res<FruitCatalog>) {
+    for side in Side::BOTH {
+        spawn_container_walls(&mut commands, side.center_x(), side);
+        commands.spawn(VersusBoard::new(side, versus.seed, &catalog));
+    }
+}
+
+// everything of the match carries its Side
+pub fn end_versus(mut commands: Commands, versus_entities: Query<Entity, With<Side>>) {
+    for entity in versus_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<VersusMatch>();
+}
+
+// a rematch on a fresh seed, the containers stay
+pub fn restart_versus(
+    mut commands: Commands,
+    fruits: Query<Entity, (With<Fruit>, With<Side>)>,
+    boards: Query<(Entity, &Side), With<ScoreTracker>>,
+    mut versus: ResMut<VersusMatch>,
+    catalog: Res<FruitCatalog>,
+) {
+    for fruit in fruits.iter() {
+        commands.entity(fruit).despawn_recursive();
+    }
+    *versus = VersusMatch::new(None);
+    for (board, side) in boards.iter() {
+        commands
+            .entity(board)
+            .insert(VersusBoard::new(*side, versus.seed, &catalog));
+    }
+}
+
+// works like drop_fruit, on the board of the requesting side
+#[allow(clippy::type_complexity)]
+pub fn versus_drop(
+    mut commands: Commands,
+    mut drop_requests: EventReader<VersusDrop>,
+    mut fruit_dropped: EventWriter<FruitDropped>,
+    mut boards: Query<(
+        &Side,
+        &mut SpawnTime,
+        &mut NextGenerator,
+        &mut GameRng,
+        &mut ScoreTracker,
+    )>,
+    fixed_time: Res<FixedTime>,
+    catalog: Res<FruitCatalog>,
+) {
+    for (_, mut spawn_time, _, _, _) in boards.iter_mut() {
+        spawn_time.timer.tick(fixed_time.period);
+    }
+    for drop_request in drop_requests.iter() {
+        let Some((side, mut spawn_time, mut next_generator, mut game_rng, mut score_tracker)) =
+            boards
+                .iter_mut()
+                .find(|(side, _, _, _, _)| **side == drop_request.side)
+        else {
+            continue;
+        };
+        if !spawn_time.timer.finished() {
+            continue;
+        }
+        spawn_time.start_new_timer(GameMode::Classic);
+        let fruit = next_generator.current_fruit;
+        score_tracker.reached_tier(fruit.tier);
+        score_tracker.drops += 1;
+        next_generator.next(&mut game_rng, &catalog);
+        let pos_x = drop_request.x + game_rng.rng().gen_range(-1.0..1.0);
+        let entity = commands
+            .spawn((
+                create_fruit_bundle_at(side.center_x(), pos_x, SPAWN_HEIGHT, fruit, &catalog),
+                AdditionalMassProperties::Mass(MASS),
+                *side,
+            ))
+            .id();
+        fruit_dropped.send(FruitDropped {
+            entity,
+            fruit,
+            x: drop_request.x,
+        });
+    }
+}
+
+// two of the largest fruits vanishing counts as a merge into the tier above them
+pub fn send_garbage(
+    mut fruits_merged: EventReader<FruitsMerged>,
+    mut boards: Query<(&Side, &mut Garbage)>,
+) {
+    for merged in fruits_merged.iter() {
+        let Some(side) = merged.side else {
+            continue;
+        };
+        let tier = merged.source.tier + 1;
+        if tier < GARBAGE_MIN_TIER {
+            continue;
+        }
+        for (board, mut garbage) in boards.iter_mut() {
+            if *board == side.opponent() {
+                garbage.receive((tier + 1 - GARBAGE_MIN_TIER) as u32);
+            }
+        }
+    }
+}
+
+// garbage is the smallest fruit, spread evenly over the container
+pub fn drop_garbage(
+    mut commands: Commands,
+    mut boards: Query<(&Side, &mut Garbage)>,
+    fixed_time: Res<FixedTime>,
+    catalog: Res<FruitCatalog>,
+) {
+    let fruit = Fruit { tier: 0 };
+    let span = CONTAINER_WIDTH / 2.0 - catalog.kind(&fruit).size / 2.0 - 1.0;
+    for (side, mut garbage) in boards.iter_mut() {
+        if garbage.pending == 0 || !garbage.timer.tick(fixed_time.period).finished() {
+            continue;
+        }
+        let count = garbage.pending.min(GARBAGE_BATCH);
+        garbage.pending -= count;
+        garbage.timer.reset();
+        for i in 0..count {
+            let pos_x = -span + 2.0 * span * (i as f32 + 0.5) / count as f32;
+            commands.spawn((
+                create_fruit_bundle_at(side.center_x(), pos_x, SPAWN_HEIGHT, fruit, &catalog),
+                AdditionalMassProperties::Mass(MASS),
+                *side,
+            ));
+        }
+    }
+}
+
+// the first side to trip the game over rules loses
+#[allow(clippy::type_complexity)]
+pub fn check_versus_over(
+    fruits: Query<(&Side, &Transform), (With<Fruit>, With<Alive>)>,
+    mut boards: Query<(&Side, &mut DangerCountdown)>,
+    grace: Res<GameOverGrace>,
+    fixed_time: Res<FixedTime>,
+    mut versus: ResMut<VersusMatch>,
+    mut versus_over: EventWriter<VersusOver>,
+) {
+    let mut losers = Vec::new();
+    for (side, mut countdown) in boards.iter_mut() {
+        let positions = fruits
+            .iter()
+            .filter(|(fruit_side, _)| *fruit_side == side)
+            .map(|(_, transform)| transform);
+        if pile_over(
+            positions,
+            side.center_x(),
+            &mut countdown,
+            &grace,
+            fixed_time.period.as_secs_f32(),
+        ) {
+            losers.push(*side);
+        }
+    }
+    let outcome = match losers.as_slice() {
+        [] => return,
+        [loser] => VersusOutcome::Won(loser.opponent()),
+        _ => VersusOutcome::Draw,
+    };
+    versus.outcome = Some(outcome);
+    versus_over.send(VersusOver { outcome });
+}
+
+pub fn freeze_versus_fruits(mut fruits: Query<&mut RigidBody, (With<Fruit>, With<Side>)>) {
+    for mut fruit in fruits.iter_mut() {
+        *fruit = RigidBody::Fixed;
+    }
+}
@@ -0,0 +1,158 @@
+// a file per key under the config dir natively, localStorage in the browser
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path_for(key: &str) -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("suika").join(format!("{}.ron", key)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path_for(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let Some(path) = path_for(key) else {
+        bevy::log::warn!("no config directory to save {} to", key);
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, value));
+    if let Err(err) = written {
+        bevy::log::warn!("could not save {}: {}", key, err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(key: &str) {
+    if let Some(path) = path_for(key) {
+        // nothing to remove is fine
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("suika.{}", key))
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    let saved = local_storage().map(|storage| storage.set_item(&format!("suika.{}", key), value));
+    if !matches!(saved, Some(Ok(()))) {
+        bevy::log::warn!("could not save {} to localStorage", key);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(&format!("suika.{}", key));
+    }
+}
+
+pub fn load_ron<T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+    let ron = load(key)?;
+    match ron::from_str(&ron) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            bevy::log::warn!("ignoring stored {}: {}", key, err);
+            None
+        }
+    }
+}
+
+pub fn save_ron<T: serde::Serialize>(key: &str, value: &T) {
+    match ron::to_string(value) {
+        Ok(ron) => save(key, &ron),
+        Err(err) => bevy::log::warn!("could not serialize {}: {}", key, err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs())
+}
+
+// SystemTime isn't available on wasm32-unknown-unknown
+#[cfg(target_arch = "wasm32")]
+pub fn now_unix_secs() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+pub fn utc_date(unix_secs: u64) -> (i64, u32, u32) {
+    // days to civil date, from Howard Hinnant's date algorithms
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn format_date(unix_secs: u64) -> String {
+    let (year, month, day) = utc_date(unix_secs);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_date_of_the_epoch() {
+        assert_eq!(utc_date(0), (1970, 1, 1));
+        assert_eq!(utc_date(86_399), (1970, 1, 1));
+        assert_eq!(utc_date(86_400), (1970, 1, 2));
+    }
+
+    #[test]
+    fn utc_date_rolls_over_months_and_years() {
+        // 2023-12-31 23:59:59 and a second later
+        assert_eq!(utc_date(1_704_067_199), (2023, 12, 31));
+        assert_eq!(utc_date(1_704_067_200), (2024, 1, 1));
+        // 2024-01-31 23:59:59 and a second later
+        assert_eq!(utc_date(1_706_745_599), (2024, 1, 31));
+        assert_eq!(utc_date(1_706_745_600), (2024, 2, 1));
+    }
+
+    #[test]
+    fn utc_date_knows_leap_years() {
+        assert_eq!(utc_date(1_709_164_800), (2024, 2, 29));
+        assert_eq!(utc_date(1_709_251_200), (2024, 3, 1));
+        // 2023-02-28 and the day after
+        assert_eq!(utc_date(1_677_542_400), (2023, 2, 28));
+        assert_eq!(utc_date(1_677_628_800), (2023, 3, 1));
+        // 2000 is a leap year, 2100 isn't
+        assert_eq!(utc_date(951_782_400), (2000, 2, 29));
+        assert_eq!(utc_date(4_107_456_000), (2100, 2, 28));
+        assert_eq!(utc_date(4_107_542_400), (2100, 3, 1));
+    }
+
+    #[test]
+    fn dates_are_formatted_with_padding() {
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        assert_eq!(format_date(0), "1970-01-01");
+    }
+}
@@ -0,0 +1,502 @@
+use bevy::{prelude::*, render::camera::ScalingMode, window::PrimaryWindow};
+
+use crate::catalog::FruitCatalog;
+use crate::constants::{
+    AIM_COARSE_SPEED, AIM_FINE_SPEED, CONTAINER_HEIGHT, CONTAINER_WIDTH, DANGER_COLOR,
+    GAME_OVER_HINT_COLOR, NEXT_PREVIEW_LABEL_SIZE, NEXT_PREVIEW_OFFSET, PREVIEW_HINT_COLOR,
+    PREVIEW_PANEL_HEIGHT, SCORE_TEXT_COLOR, SCREEN_HEIGHT, SPAWN_HEIGHT, SPAWN_OFFSET, TEXT_COLOR,
+    VERSUS_ARENA_X, VERSUS_WIDTH,
+};
+use crate::gamepad::{any_gamepad_just_pressed, STICK_DEADZONE};
+use crate::helpers::get_mouse_pos;
+use crate::menu::ControlsPanel;
+use crate::resources::{Aim, DangerCountdown, NextGenerator, NextSeed, ScoreTracker};
+use crate::setup::{game_over_line_bundle, spawn_container, spawn_preview_panel, MainCamera};
+use crate::simulation::{
+    pos_x_in_bounds, versus_playing, Garbage, RestartGame, Side, VersusDrop, VersusMatch,
+    VersusOutcome,
+};
+use crate::AppState;
+
+// left player on the keyboard, right one on the mouse or a gamepad
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            start_versus
+                .run_if(in_state(AppState::StartMenu).or_else(in_state(AppState::GameOverMenu))),
+        )
+        .add_systems(OnEnter(AppState::Versus), (setup_versus_view, focus_arena))
+        .add_systems(
+            Update,
+            (
+                keyboard_versus_input,
+                pointer_versus_input,
+                gamepad_versus_input,
+            )
+                .run_if(in_state(AppState::Versus).and_then(versus_playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                versus_menu_input,
+                update_versus_previews,
+                update_versus_scores,
+                update_versus_lines,
+                show_versus_outcome.run_if(resource_exists_and_changed::<VersusMatch>()),
+            )
+                .run_if(in_state(AppState::Versus)),
+        )
+        .add_systems(OnExit(AppState::Versus), cleanup_versus_view);
+    }
+}
+
+#[derive(Component, Clone)]
+struct VersusView;
+
+#[derive(Component)]
+struct VersusPreview(Side);
+
+#[derive(Component)]
+struct VersusNextPreview(Side);
+
+#[derive(Component)]
+struct VersusScore(Side);
+
+#[derive(Component)]
+struct VersusLine(Side);
+
+#[derive(Component)]
+struct VersusBanner;
+
+// V on the start or game over screen, on the typed seed if there is one
+fn start_versus(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_seed: ResMut<NextSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::V) {
+        return;
+    }
+    commands.insert_resource(VersusMatch::new(next_seed.seed.take()));
+    next_state.set(AppState::Versus);
+}
+
+fn setup_versus_view(
+    mut commands: Commands,
+    mut controls: Query<&mut Visibility, With<ControlsPanel>>,
+) {
+    // the in-game controls are for the regular run
+    for mut visibility in controls.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    for side in Side::BOTH {
+        let center_x = side.center_x();
+        spawn_container(&mut commands, center_x, VersusView);
+        commands.spawn((
+            VersusView,
+            VersusLine(side),
+            game_over_line_bundle(center_x),
+        ));
+
+        commands
+            .spawn((
+                VersusView,
+                VersusPreview(side),
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::ZERO),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(center_x, SPAWN_HEIGHT, 0.0),
+                    ..default()
+                },
+            ))
+            .with_children(|builder| {
+                let hint = SPAWN_OFFSET - 45.0;
+                builder.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(5.0, hint)),
+                        color: PREVIEW_HINT_COLOR,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, -hint / 2.0, -1.0),
+                    ..default()
+                });
+            });
+
+        // the panels go on the outer side of each container
+        let outward = match side {
+            Side::Left => -1.0,
+            Side::Right => 1.0,
+        };
+        let panel = Vec2::new(
+            center_x + outward * (CONTAINER_WIDTH / 2.0 + NEXT_PREVIEW_OFFSET),
+            CONTAINER_HEIGHT / 2.0,
+        );
+        spawn_preview_panel(
+            &mut commands,
+            (VersusView, VersusNextPreview(side)),
+            "NEXT",
+            panel,
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::ZERO),
+                    ..default()
+                },
+                ..default()
+            },
+        );
+
+        let score_y = panel.y - PREVIEW_PANEL_HEIGHT / 2.0 - 20.0;
+        commands.spawn((
+            VersusView,
+            VersusScore(side),
+            Text2dBundle {
+                text: Text::from_sections([
+                    TextSection::new(
+                        "0",
+                        TextStyle {
+                            font_size: NEXT_PREVIEW_LABEL_SIZE,
+                            color: SCORE_TEXT_COLOR,
+                            ..default()
+                        },
+                    ),
+                    // garbage on its way in
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: DANGER_COLOR,
+                            ..default()
+                        },
+                    ),
+                ])
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(panel.x, score_y, 1.0),
+                ..default()
+            },
+        ));
+
+        let controls = match side {
+            Side::Left => "A / D to aim\nS or space to drop",
+            Side::Right => "mouse or gamepad\nto aim and drop",
+        };
+        commands.spawn((
+            VersusView,
+            Text2dBundle {
+                text: Text::from_section(
+                    controls,
+                    TextStyle {
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(panel.x, score_y - 120.0, 1.0),
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        VersusView,
+        VersusBanner,
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        font_size: 50.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TextSection::new(
+                    "\nR or start for a rematch, esc to leave",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+            ])
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_xyz(VERSUS_ARENA_X, SCREEN_HEIGHT / 2.0 - 70.0, 5.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+// fits both containers and their panels
+fn focus_arena(mut camera: Query<(&mut OrthographicProjection, &mut Transform), With<MainCamera>>) {
+    if let Ok((mut projection, mut transform)) = camera.get_single_mut() {
+        projection.scaling_mode = ScalingMode::AutoMin {
+            min_width: VERSUS_WIDTH,
+            min_height: SCREEN_HEIGHT,
+        };
+        transform.translation.x = VERSUS_ARENA_X;
+        transform.translation.y = 0.0;
+    }
+}
+
+fn cleanup_versus_view(
+    mut commands: Commands,
+    view: Query<Entity, With<VersusView>>,
+    mut controls: Query<&mut Visibility, With<ControlsPanel>>,
+) {
+    for entity in view.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for mut visibility in controls.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// the left player: A / D aim (faster with shift), S or space drops
+fn keyboard_versus_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut boards: Query<(&Side, &mut Aim, &NextGenerator)>,
+    catalog: Res<FruitCatalog>,
+    mut drop_requests: EventWriter<VersusDrop>,
+) {
+    let Some((_, mut aim, next_generator)) =
+        boards.iter_mut().find(|(side, _, _)| **side == Side::Left)
+    else {
+        return;
+    };
+
+    let mut direction = 0.0;
+    if keyboard_input.pressed(KeyCode::A) {
+        direction -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::D) {
+        direction += 1.0;
+    }
+    if direction != 0.0 {
+        let speed = if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            AIM_COARSE_SPEED
+        } else {
+            AIM_FINE_SPEED
+        };
+        aim.x = pos_x_in_bounds(
+            aim.x + direction * speed * time.delta_seconds(),
+            catalog.kind(&next_generator.current_fruit).size,
+        );
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::S, KeyCode::Space]) {
+        drop_requests.send(VersusDrop {
+            side: Side::Left,
+            x: aim.x,
+        });
+    }
+}
+
+// only aims while moving so it doesn't fight a gamepad
+fn pointer_versus_input(
+    mut cursor_moved: EventReader<CursorMoved>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    q_windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut boards: Query<(&Side, &mut Aim, &NextGenerator)>,
+    catalog: Res<FruitCatalog>,
+    mut drop_requests: EventWriter<VersusDrop>,
+) {
+    let Some((_, mut aim, next_generator)) =
+        boards.iter_mut().find(|(side, _, _)| **side == Side::Right)
+    else {
+        return;
+    };
+
+    if cursor_moved.iter().last().is_some() {
+        if let Some(world_position) = get_mouse_pos(&q_windows, &camera_q) {
+            aim.x = pos_x_in_bounds(
+                world_position.x - Side::Right.center_x(),
+                catalog.kind(&next_generator.current_fruit).size,
+            );
+        }
+    }
+
+    if mouse_button_input.just_pressed(MouseButton::Left) {
+        drop_requests.send(VersusDrop {
+            side: Side::Right,
+            x: aim.x,
+        });
+    }
+}
+
+// the right player's gamepad, any connected one: stick or d-pad aims, south drops
+fn gamepad_versus_input(
+    gamepads: Res<Gamepads>,
+    button_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    mut boards: Query<(&Side, &mut Aim, &NextGenerator)>,
+    catalog: Res<FruitCatalog>,
+    mut drop_requests: EventWriter<VersusDrop>,
+) {
+    let Some((_, mut aim, next_generator)) =
+        boards.iter_mut().find(|(side, _, _)| **side == Side::Right)
+    else {
+        return;
+    };
+
+    let mut movement = 0.0;
+    for gamepad in gamepads.iter() {
+        let stick = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.0);
+        if stick.abs() > STICK_DEADZONE {
+            movement += stick * AIM_COARSE_SPEED;
+        }
+        if button_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft)) {
+            movement -= AIM_FINE_SPEED;
+        }
+        if button_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadRight)) {
+            movement += AIM_FINE_SPEED;
+        }
+    }
+    if movement != 0.0 {
+        aim.x = pos_x_in_bounds(
+            aim.x + movement * time.delta_seconds(),
+            catalog.kind(&next_generator.current_fruit).size,
+        );
+    }
+
+    if any_gamepad_just_pressed(&gamepads, &button_input, [GamepadButtonType::South]) {
+        drop_requests.send(VersusDrop {
+            side: Side::Right,
+            x: aim.x,
+        });
+    }
+}
+
+// R, enter or start for a rematch once decided, esc or select leaves any time
+fn versus_menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    versus: Res<VersusMatch>,
+    mut restart: EventWriter<RestartGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::Select])
+    {
+        next_state.set(AppState::StartMenu);
+        return;
+    }
+    if versus.outcome.is_some()
+        && (keyboard_input.any_just_pressed([KeyCode::R, KeyCode::Return])
+            || any_gamepad_just_pressed(&gamepads, &gamepad_input, [GamepadButtonType::Start]))
+    {
+        restart.send(RestartGame);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_versus_previews(
+    boards: Query<(&Side, &Aim, Ref<NextGenerator>)>,
+    mut previews: Query<(
+        &VersusPreview,
+        &mut Transform,
+        &mut Sprite,
+        &mut Handle<Image>,
+    )>,
+    mut next_previews: Query<
+        (&VersusNextPreview, &mut Sprite, &mut Handle<Image>),
+        Without<VersusPreview>,
+    >,
+    asset_server: Res<AssetServer>,
+    catalog: Res<FruitCatalog>,
+) {
+    for (side, aim, next_generator) in boards.iter() {
+        let current = catalog.kind(&next_generator.current_fruit);
+        for (preview, mut transform, mut sprite, mut handle) in previews.iter_mut() {
+            if preview.0 != *side {
+                continue;
+            }
+            transform.translation.x = side.center_x() + pos_x_in_bounds(aim.x, current.size);
+            if next_generator.is_changed() {
+                sprite.custom_size = Some(Vec2::new(1.0, 1.0) * current.size);
+                *handle = asset_server.load(&current.texture);
+            }
+        }
+
+        if !next_generator.is_changed() {
+            continue;
+        }
+        let next = catalog.kind(&next_generator.next_fruit);
+        for (next_preview, mut sprite, mut handle) in next_previews.iter_mut() {
+            if next_preview.0 == *side {
+                sprite.custom_size = Some(Vec2::new(1.0, 1.0) * next.size);
+                *handle = asset_server.load(&next.texture);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_versus_scores(
+    boards: Query<(&Side, &ScoreTracker, &Garbage), Or<(Changed<ScoreTracker>, Changed<Garbage>)>>,
+    mut scores: Query<(&VersusScore, &mut Text)>,
+) {
+    for (side, score_tracker, garbage) in boards.iter() {
+        for (score, mut text) in scores.iter_mut() {
+            if score.0 != *side {
+                continue;
+            }
+            text.sections[0].value = score_tracker.score.to_string();
+            text.sections[1].value = match garbage.pending {
+                0 => String::new(),
+                pending => format!("\n{} incoming", pending),
+            };
+        }
+    }
+}
+
+// a side's line is red while its pile is over it
+fn update_versus_lines(
+    boards: Query<(&Side, &DangerCountdown)>,
+    mut lines: Query<(&VersusLine, &mut Sprite)>,
+) {
+    for (side, countdown) in boards.iter() {
+        let color = if countdown.elapsed.is_some() {
+            DANGER_COLOR
+        } else {
+            GAME_OVER_HINT_COLOR
+        };
+        for (line, mut sprite) in lines.iter_mut() {
+            if line.0 == *side && sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+fn show_versus_outcome(
+    versus: Res<VersusMatch>,
+    mut banner: Query<(&mut Text, &mut Visibility), With<VersusBanner>>,
+) {
+    let Ok((mut text, mut visibility)) = banner.get_single_mut() else {
+        return;
+    };
+    match versus.outcome {
+        Some(outcome) => {
+            text.sections[0].value = match outcome {
+                VersusOutcome::Won(side) => format!("{} WINS", side.name().to_uppercase()),
+                VersusOutcome::Draw => String::from("DRAW"),
+            };
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
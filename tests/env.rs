@@ -0,0 +1,84 @@
+// SuikaEnv driven the way an agent would, a drop at a time
+use suika::env::{Observation, SuikaEnv};
+
+const DROPS: [f32; 6] = [-150.0, 150.0, 0.0, -80.0, 80.0, 40.0];
+
+fn play(seed: u64) -> Vec<Observation> {
+    let mut env = SuikaEnv::new();
+    let mut observations = vec![env.reset(seed)];
+    for x in DROPS {
+        observations.push(env.step(x).0);
+    }
+    observations
+}
+
+#[test]
+fn steps_drop_the_current_fruit() {
+    let mut env = SuikaEnv::new();
+    let start = env.reset(3);
+    assert!(start.fruits.is_empty());
+    assert_eq!(start.score, 0);
+    assert!(!env.is_done());
+
+    let (observation, reward, done) = env.step(-100.0);
+    assert!(!done);
+    assert_eq!(reward, 0.0);
+    assert_eq!(observation.fruits.len(), 1);
+    let dropped = &observation.fruits[0];
+    assert_eq!(dropped.fruit, start.current);
+    assert!((dropped.position.x + 100.0).abs() < 40.0);
+    // the step only returns once the fruit landed
+    assert!(dropped.velocity.length() < 5.0);
+    assert_eq!(observation.current, start.next);
+
+    let mut score = observation.score;
+    for x in DROPS {
+        let (observation, reward, _) = env.step(x);
+        assert_eq!(reward, (observation.score - score) as f32);
+        score = observation.score;
+    }
+}
+
+#[test]
+fn run_ends_once_the_container_overflows() {
+    let mut env = SuikaEnv::new();
+    env.reset(5);
+    let mut done = false;
+    for _ in 0..300 {
+        done = env.step(0.0).2;
+        if done {
+            break;
+        }
+    }
+    assert!(done, "the container never overflowed");
+    assert!(env.is_done());
+    let (_, reward, done) = env.step(0.0);
+    assert!(done);
+    assert_eq!(reward, 0.0);
+
+    let observation = env.reset(5);
+    assert!(!env.is_done());
+    assert!(observation.fruits.is_empty());
+    assert_eq!(observation.score, 0);
+}
+
+#[test]
+fn same_seed_plays_out_the_same() {
+    let first = play(9);
+    let second = play(9);
+    for (a, b) in first.iter().zip(second.iter()) {
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.current, b.current);
+        assert_eq!(a.next, b.next);
+        assert_eq!(a.fruits.len(), b.fruits.len());
+        for (a, b) in a.fruits.iter().zip(b.fruits.iter()) {
+            assert_eq!(a.fruit, b.fruit);
+            assert_eq!(a.position, b.position);
+        }
+    }
+    let other = play(10);
+    assert!(first
+        .iter()
+        .zip(other.iter())
+        .any(|(a, b)| a.current != b.current || a.next != b.next));
+}
@@ -0,0 +1,187 @@
+// races against a relay running in this process
+
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use suika::race::protocol::{ClientMessage, Progress, ServerMessage};
+use suika::race::{run_relay, Race, RaceConnection, RacePlugin, RaceSettings};
+use suika::resources::GameRng;
+use suika::simulation::headless_app;
+use suika::AppState;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn start_relay() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    thread::spawn(move || run_relay(listener));
+    url
+}
+
+fn join(url: &str, room: &str, name: &str) -> RaceConnection {
+    let mut connection = RaceConnection::open(url);
+    connection.send(&ClientMessage::Join {
+        room: room.to_string(),
+        name: name.to_string(),
+    });
+    connection
+}
+
+// the first message matching `wanted`, skipping the others
+fn wait_for(
+    connection: &mut RaceConnection,
+    mut wanted: impl FnMut(&ServerMessage) -> bool,
+) -> ServerMessage {
+    let start = Instant::now();
+    while start.elapsed() < TIMEOUT {
+        if let Some(message) = connection.receive().into_iter().find(&mut wanted) {
+            return message;
+        }
+        assert!(!connection.is_closed(), "the relay closed the connection");
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("no matching message from the relay in time");
+}
+
+fn welcomed_id(connection: &mut RaceConnection) -> u32 {
+    match wait_for(connection, |message| {
+        matches!(message, ServerMessage::Welcome { .. })
+    }) {
+        ServerMessage::Welcome { id, .. } => id,
+        _ => unreachable!(),
+    }
+}
+
+// runs the app in real time until `done` holds
+fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) {
+    let start = Instant::now();
+    while !done(&mut app.world) {
+        assert!(start.elapsed() < TIMEOUT, "the app never got there");
+        app.update();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn relay_passes_messages_within_a_room() {
+    let url = start_relay();
+    let mut first = join(&url, "room", "first");
+    let first_id = welcomed_id(&mut first);
+    let mut second = join(&url, "room", "second");
+    let mut elsewhere = join(&url, "elsewhere", "third");
+
+    let second_id = match wait_for(&mut second, |message| {
+        matches!(message, ServerMessage::Welcome { .. })
+    }) {
+        ServerMessage::Welcome { id, players } => {
+            assert_eq!(players.len(), 1);
+            assert_eq!(players[0].id, first_id);
+            assert_eq!(players[0].name, "first");
+            id
+        }
+        _ => unreachable!(),
+    };
+    assert_ne!(first_id, second_id);
+    let joined = wait_for(&mut first, |message| {
+        matches!(message, ServerMessage::Joined(_))
+    });
+    assert!(matches!(joined, ServerMessage::Joined(player) if player.id == second_id));
+    // nobody else was in the other room
+    assert!(matches!(
+        wait_for(&mut elsewhere, |message| matches!(message, ServerMessage::Welcome { .. })),
+        ServerMessage::Welcome { players, .. } if players.is_empty()
+    ));
+
+    first.send(&ClientMessage::Start { seed: Some(42) });
+    for connection in [&mut first, &mut second] {
+        let started = wait_for(connection, |message| {
+            matches!(message, ServerMessage::Started { .. })
+        });
+        assert_eq!(started, ServerMessage::Started { seed: 42 });
+    }
+
+    let progress = Progress {
+        score: 120,
+        drops: 7,
+        ..default()
+    };
+    second.send(&ClientMessage::Progress(progress.clone()));
+    let relayed = wait_for(&mut first, |message| {
+        matches!(message, ServerMessage::Progress { .. })
+    });
+    assert_eq!(
+        relayed,
+        ServerMessage::Progress {
+            id: second_id,
+            progress
+        }
+    );
+
+    drop(second);
+    let left = wait_for(&mut first, |message| {
+        matches!(message, ServerMessage::Left { .. })
+    });
+    assert_eq!(left, ServerMessage::Left { id: second_id });
+
+    // the other room heard none of it
+    thread::sleep(Duration::from_millis(100));
+    assert!(elsewhere.receive().is_empty());
+}
+
+#[test]
+fn headless_app_races_on_the_shared_seed() {
+    let url = start_relay();
+    let mut app = headless_app();
+    app.add_plugins(RacePlugin).insert_resource(RaceSettings {
+        url: url.clone(),
+        room: String::from("race"),
+        name: String::from("bot"),
+    });
+    update_until(&mut app, |world| world.resource::<Race>().id.is_some());
+    let app_id = app.world.resource::<Race>().id.unwrap();
+
+    let mut opponent = join(&url, "race", "opponent");
+    let opponent_id = welcomed_id(&mut opponent);
+    update_until(&mut app, |world| {
+        world.resource::<Race>().opponents.len() == 1
+    });
+
+    opponent.send(&ClientMessage::Start { seed: Some(42) });
+    update_until(&mut app, |world| {
+        *world.resource::<State<AppState>>().get() == AppState::InGame
+    });
+    assert_eq!(app.world.resource::<Race>().seed, Some(42));
+    assert_eq!(app.world.resource::<GameRng>().seed, 42);
+
+    // the app reports its run to the room a few times a second
+    let start = Instant::now();
+    let mut reported = false;
+    while !reported {
+        assert!(start.elapsed() < TIMEOUT, "the app never sent its progress");
+        app.update();
+        reported = opponent
+            .receive()
+            .into_iter()
+            .any(|message| matches!(message, ServerMessage::Progress { id, .. } if id == app_id));
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    opponent.send(&ClientMessage::Progress(Progress {
+        score: 300,
+        ..default()
+    }));
+    update_until(&mut app, |world| {
+        world
+            .resource::<Race>()
+            .opponents
+            .iter()
+            .any(|opponent| opponent.id == opponent_id && opponent.progress.score == 300)
+    });
+
+    drop(opponent);
+    update_until(&mut app, |world| {
+        world.resource::<Race>().opponents.is_empty()
+    });
+}
@@ -0,0 +1,98 @@
+// a run recorded through the replay plugin, played back at another frame rate
+use std::time::Duration;
+
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use suika::constants::SIMULATION_STEP;
+use suika::env::SuikaEnv;
+use suika::replay::{Replay, ReplayPlayback, ReplayPlugin, ReplayRecorder};
+use suika::resources::{NextSeed, RunClock, ScoreTracker};
+use suika::simulation::{at_rest, headless_app, settle_until, SettleConfig, TimeSinceSpawn};
+use suika::{AppState, Fruit};
+
+const SEED: u64 = 7;
+const DROPS: [f32; 12] = [
+    -150.0, 80.0, 0.0, 120.0, -60.0, 30.0, -120.0, 150.0, -20.0, 60.0, -90.0, 10.0,
+];
+
+// score, step of the run and every fruit on the board
+type Board = (u32, u32, Vec<(usize, Vec2)>);
+
+fn board(app: &mut App) -> Board {
+    let score = app.world.resource::<ScoreTracker>().score;
+    let frame = app.world.resource::<RunClock>().frame;
+    let mut fruits = app.world.query::<(&Fruit, &Transform)>();
+    let mut fruits: Vec<_> = fruits
+        .iter(&app.world)
+        .map(|(fruit, transform)| (fruit.tier, transform.translation.truncate()))
+        .collect();
+    fruits.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+    (score, frame, fruits)
+}
+
+fn record() -> (Replay, Board) {
+    let mut env = SuikaEnv::new();
+    env.app_mut().add_plugins((InputPlugin, ReplayPlugin));
+    env.reset(SEED);
+    for x in DROPS {
+        let (_, _, done) = env.step(x);
+        assert!(!done, "the recorded run ended early");
+    }
+    let recorded = board(env.app_mut());
+    let replay = env
+        .app_mut()
+        .world
+        .resource::<ReplayRecorder>()
+        .replay
+        .clone();
+    assert_eq!(replay.seed, SEED);
+    assert_eq!(replay.drops.len(), DROPS.len());
+    assert!(recorded.0 > 0);
+    (replay, recorded)
+}
+
+// three fixed steps a frame, like a replay watched at 3x
+fn play_back(replay: Replay) -> App {
+    let mut app = headless_app();
+    app.add_plugins((InputPlugin, ReplayPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            3.0 * SIMULATION_STEP,
+        )))
+        .insert_resource(NextSeed { seed: Some(SEED) })
+        .insert_resource(ReplayPlayback::new(replay));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Replay);
+    for _ in 0..60 * 60 {
+        app.update();
+        if app.world.resource::<ReplayPlayback>().finished() {
+            break;
+        }
+    }
+    assert!(app.world.resource::<ReplayPlayback>().finished());
+    app
+}
+
+#[test]
+fn replay_ends_on_the_recorded_board() {
+    let (replay, recorded) = record();
+    let mut app = play_back(replay);
+
+    // the recorded run stopped on the first step with the last drop landed and the board at rest
+    let config = SettleConfig::default();
+    settle_until(&mut app, &config, |world| {
+        let mut spawning = world.query_filtered::<(), With<TimeSinceSpawn>>();
+        spawning.iter(world).next().is_none() && at_rest(world, &config)
+    });
+    assert!(app.world.resource::<ReplayPlayback>().in_sync());
+    assert_eq!(board(&mut app), recorded);
+}
+
+#[test]
+fn replay_of_other_fruits_is_out_of_sync() {
+    let (mut replay, _) = record();
+    replay.drops[3].tier += 1;
+    let app = play_back(replay);
+    assert!(!app.world.resource::<ReplayPlayback>().in_sync());
+}
@@ -0,0 +1,94 @@
+// settling a headless app after drops, on the fixed step
+use bevy::prelude::*;
+use suika::resources::{NextSeed, RunClock, ScoreTracker, SpawnTime};
+use suika::simulation::{
+    at_rest, headless_app, settle, settle_until, DropFruit, SettleConfig, SettleOutcome,
+};
+use suika::{AppState, Fruit};
+
+fn started_app(seed: u64) -> App {
+    let mut app = headless_app();
+    app.insert_resource(NextSeed { seed: Some(seed) });
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    app.update();
+    app
+}
+
+fn fruits(app: &mut App) -> Vec<(usize, Vec2)> {
+    let mut fruits = app.world.query::<(&Fruit, &Transform)>();
+    fruits
+        .iter(&app.world)
+        .map(|(fruit, transform)| (fruit.tier, transform.translation.truncate()))
+        .collect()
+}
+
+// drops a fruit once the previous one is allowed, then settles
+fn drop_and_settle(app: &mut App, x: f32, config: &SettleConfig) -> SettleOutcome {
+    app.world.send_event(DropFruit { x });
+    settle_until(app, config, |world| {
+        world.resource::<SpawnTime>().timer.finished() && at_rest(world, config)
+    })
+}
+
+#[test]
+fn dropped_fruit_settles_within_the_budget() {
+    let config = SettleConfig::default();
+    let mut app = started_app(1);
+    app.world.send_event(DropFruit { x: 0.0 });
+    // the fruit has to fall first, so the board isn't at rest right away
+    let outcome = settle_until(&mut app, &config, |world| {
+        world.resource::<RunClock>().frame > 10 && at_rest(world, &config)
+    });
+    let SettleOutcome::AtRest { steps } = outcome else {
+        panic!("the board didn't settle: {:?}", outcome);
+    };
+    assert!(steps <= config.max_steps);
+    assert!(at_rest(&mut app.world, &config));
+
+    let fruits = fruits(&mut app);
+    assert_eq!(fruits.len(), 1);
+    // resting on the bottom of the container, well below where it was dropped
+    assert!(fruits[0].1.y < -300.0);
+    // a board at rest settles again after a single step
+    assert_eq!(
+        settle(&mut app, &config),
+        SettleOutcome::AtRest { steps: 1 }
+    );
+}
+
+#[test]
+fn step_cap_stops_settling() {
+    let config = SettleConfig {
+        max_steps: 5,
+        ..default()
+    };
+    let mut app = started_app(1);
+    app.world.send_event(DropFruit { x: 0.0 });
+    assert_eq!(
+        settle_until(&mut app, &config, |_| false),
+        SettleOutcome::StepCap
+    );
+    assert_eq!(app.world.resource::<RunClock>().frame, 5);
+}
+
+#[test]
+fn same_seed_settles_the_same() {
+    let config = SettleConfig::default();
+    let play = |seed| {
+        let mut app = started_app(seed);
+        let outcomes: Vec<SettleOutcome> = [-120.0, 60.0, 0.0, 130.0, -40.0]
+            .into_iter()
+            .map(|x| drop_and_settle(&mut app, x, &config))
+            .collect();
+        let score = app.world.resource::<ScoreTracker>().score;
+        (outcomes, score, fruits(&mut app))
+    };
+    let first = play(21);
+    assert!(first
+        .0
+        .iter()
+        .all(|outcome| matches!(outcome, SettleOutcome::AtRest { .. })));
+    assert_eq!(play(21), first);
+}
@@ -0,0 +1,81 @@
+// a board captured mid-run and restored into a new app, through the same RON a save goes through
+use bevy::prelude::*;
+use suika::env::SuikaEnv;
+use suika::resources::{GameRng, NextGenerator, ScoreTracker};
+use suika::simulation::{capture_board, headless_app, BoardSnapshot, PendingRestore};
+use suika::{AppState, Fruit};
+
+fn played_board() -> BoardSnapshot {
+    let mut env = SuikaEnv::new();
+    env.reset(11);
+    for x in [-120.0, 40.0, 0.0, 150.0, -60.0, 90.0, -30.0, 10.0] {
+        env.step(x);
+    }
+    capture_board(&mut env.app_mut().world)
+}
+
+fn restored_app(snapshot: BoardSnapshot) -> App {
+    let mut app = headless_app();
+    app.insert_resource(PendingRestore(snapshot));
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    app.update();
+    app
+}
+
+fn to_ron(snapshot: &BoardSnapshot) -> String {
+    ron::to_string(snapshot).unwrap()
+}
+
+#[test]
+fn restored_board_captures_the_same() {
+    let snapshot = played_board();
+    assert!(!snapshot.fruits.is_empty());
+    let saved = to_ron(&snapshot);
+    let loaded: BoardSnapshot = ron::from_str(&saved).unwrap();
+
+    let mut app = restored_app(loaded);
+    assert!(!app.world.contains_resource::<PendingRestore>());
+    let restored = capture_board(&mut app.world);
+    assert_eq!(restored.fruits.len(), snapshot.fruits.len());
+    for (restored, saved) in restored.fruits.iter().zip(snapshot.fruits.iter()) {
+        assert_eq!(restored.tier, saved.tier);
+        assert_eq!(restored.position, saved.position);
+        assert!((restored.rotation - saved.rotation).abs() < 1e-4);
+        assert_eq!(restored.linvel, saved.linvel);
+        assert_eq!(restored.spawn_elapsed, saved.spawn_elapsed);
+        assert_eq!(restored.mass, saved.mass);
+    }
+    assert_eq!(restored.score, snapshot.score);
+    assert_eq!(restored.current_fruit, snapshot.current_fruit);
+    assert_eq!(restored.next_fruit, snapshot.next_fruit);
+    assert_eq!(restored.held_fruit, snapshot.held_fruit);
+    assert_eq!(restored.seed, snapshot.seed);
+    assert_eq!(restored.rng_word_pos, snapshot.rng_word_pos);
+    assert_eq!(restored.run_frame, snapshot.run_frame);
+    assert_eq!(restored.drops, snapshot.drops);
+}
+
+#[test]
+fn board_from_another_catalog_starts_a_new_run() {
+    let mut snapshot = played_board();
+    snapshot.fruits[0].tier = 99;
+    snapshot.held_fruit = Some(0);
+    let mut app = restored_app(snapshot.clone());
+    assert!(!app.world.contains_resource::<PendingRestore>());
+    let mut fruits = app.world.query::<&Fruit>();
+    assert_eq!(fruits.iter(&app.world).count(), 0);
+    assert_eq!(app.world.resource::<ScoreTracker>().score, 0);
+    assert_eq!(app.world.resource::<NextGenerator>().held_fruit, None);
+    assert_ne!(
+        app.world.resource::<GameRng>().word_pos(),
+        snapshot.rng_word_pos
+    );
+
+    // the fruit queue is checked too
+    let mut snapshot = played_board();
+    snapshot.next_fruit = 11;
+    let mut app = restored_app(snapshot);
+    assert_eq!(app.world.query::<&Fruit>().iter(&app.world).count(), 0);
+}